@@ -0,0 +1,161 @@
+use anyhow::Result;
+
+use std::fmt::Display;
+
+use crate::grid::Grid;
+
+/// A solver's answer to one part of a problem
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    /// An integer, compared exactly regardless of how large it is
+    Int(i128),
+
+    /// Free-form text
+    Str(String),
+
+    /// A picture, which usually shows some block letters
+    Grid(Grid<bool>),
+}
+
+impl Answer {
+    /// Short name for the kind of answer, as used in JSON output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Int(_) => "int",
+            Self::Str(_) => "str",
+            Self::Grid(_) => "grid",
+        }
+    }
+
+    /// The drawing, for answers which are pictures
+    pub fn art(&self) -> Option<String> {
+        match self {
+            Self::Grid(g) => Some(g.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Check that the answer is plausible
+    ///
+    /// Empty text or pictures are never answers, so they indicate a bug in the solver, even if
+    /// the solver didn't notice. Integers are only checked if they have to be `positive`, since
+    /// plenty of parts can legitimately come out as zero or less on some inputs.
+    pub fn check(&self, positive: bool) -> Result<()> {
+        match self {
+            Self::Int(n) => {
+                anyhow::ensure!(!positive || *n > 0, "Implausible answer {}: answer must be positive", n)
+            }
+            Self::Str(s) => anyhow::ensure!(!s.trim().is_empty(), "Implausible answer: empty text"),
+            Self::Grid(g) => {
+                anyhow::ensure!(g.cells().any(|b| *b), "Implausible answer: empty picture")
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the answer matches an expected one, given as text
+    ///
+    /// Integers are compared numerically, and text is compared ignoring leading and trailing
+    /// blank lines and trailing whitespace on each line. Pictures match either the text they
+    /// show, or the drawing itself.
+    pub fn matches(&self, expected: &str) -> bool {
+        match self {
+            Self::Int(n) => expected.trim().parse::<i128>().ok() == Some(*n),
+            Self::Str(s) => normalize(s) == normalize(expected),
+            Self::Grid(g) => {
+                let expected = normalize(expected);
+                crate::ocr::decode(g).ok().as_deref() == Some(expected.as_str()) ||
+                    normalize(&g.to_string()) == expected
+            }
+        }
+    }
+}
+
+/// Displays text, or the text shown in a picture if it can be read
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Int(n) => write!(f, "{}", n),
+            Self::Str(s) => f.write_str(s),
+            Self::Grid(g) => match crate::ocr::decode(g) {
+                Ok(text) => f.write_str(&text),
+                Err(_) => write!(f, "{}", g),
+            },
+        }
+    }
+}
+
+macro_rules! from_int {
+    ($($t:ty)*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(x: $t) -> Self {
+                    Self::Int(x as i128)
+                }
+            }
+        )*
+    };
+}
+
+from_int! { u8 u16 u32 u64 usize i8 i16 i32 i64 isize i128 }
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Self::Str(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Self::Str(s.to_owned())
+    }
+}
+
+impl From<Grid<bool>> for Answer {
+    fn from(g: Grid<bool>) -> Self {
+        Self::Grid(g)
+    }
+}
+
+/// Canonicalize text for comparison
+pub fn normalize(s: &str) -> String {
+    s.lines()
+     .map(|l| l.trim_end())
+     .skip_while(|l| l.is_empty())
+     .collect::<Vec<_>>()
+     .join("\n")
+     .trim_end()
+     .to_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matching() {
+        assert!(Answer::from(1514285714288u64).matches("1514285714288"));
+        assert!(Answer::from(1514285714288u64).matches(" 1514285714288\n"));
+        assert!(!Answer::from(1514285714288u64).matches("1514285714289"));
+        assert!(!Answer::from(12).matches("twelve"));
+
+        assert!(Answer::from("CMZ").matches("CMZ"));
+        assert!(!Answer::from("CMZ").matches("cmz"));
+
+        let grid = Grid::from_data(vec![true, false, true, true], 2);
+        assert!(Answer::from(grid.clone()).matches("\n# \n##\n"));
+        assert_eq!(Answer::from(grid.clone()).to_string(), grid.to_string());
+        assert!(!Answer::from(grid).matches("\n##\n##\n"));
+    }
+
+    #[test]
+    fn checks() {
+        assert!(Answer::from(3).check(true).is_ok());
+        assert!(Answer::from(0).check(true).is_err());
+        assert!(Answer::from(-4).check(true).is_err());
+        assert!(Answer::from(-4).check(false).is_ok());
+        assert!(Answer::from("").check(false).is_err());
+        assert!(Answer::from(Grid::filled(3, 3, false)).check(false).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use anyhow::{anyhow, Result};
+
+use std::collections::HashMap;
+
+use crate::answer::Answer;
+
+/// Name of the dataset used when none is given, which is normally the real puzzle input
+pub const DEFAULT_DATASET: &str = "real";
+
+/// A set of known-good answers, keyed by problem number, dataset and part
+///
+/// Answers are stored in a small subset of TOML, with one table per problem and dataset. Tables
+/// named after just the problem hold answers for the default dataset:
+///
+/// ```text
+/// # comments are allowed
+/// [05]
+/// part1 = "CMZ"
+/// part2 = "MCD"
+///
+/// [05.example1]
+/// part1 = "CMZ"
+///
+/// [10]
+/// part1 = 13140
+/// part2 = '''
+/// ##..##..
+/// '''
+/// ```
+///
+/// Values may be integers, basic strings (with the usual backslash escapes), literal strings, or
+/// multi-line literal strings.
+#[derive(Debug, Default)]
+pub struct Answers {
+    entries: HashMap<(usize, String, usize), String>,
+}
+
+/// Result of comparing a solver's answer against the expected one
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The answer matches the stored one
+    Pass,
+
+    /// The answer differs from the stored one
+    Fail { expected: String },
+
+    /// No answer is stored for this problem and part
+    Missing,
+}
+
+impl Answers {
+    /// Load answers from a file
+    pub fn load(path: &std::path::Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)
+                  .map_err(|e| anyhow!("Unable to read {}: {}", path.display(), e))?;
+        Self::parse(&data)
+    }
+
+    /// Parse answers from the contents of an answers file
+    pub fn parse(data: &str) -> Result<Self> {
+        let mut entries = HashMap::new();
+        let mut problem = None;
+        let mut lines = data.lines().enumerate();
+
+        while let Some((idx, line)) = lines.next() {
+            let line_no = idx + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let header = header.strip_suffix(']')
+                            .ok_or_else(|| anyhow!("Unterminated table header on line {}", line_no))?;
+                let (n, dataset) = header.split_once('.').unwrap_or((header, DEFAULT_DATASET));
+                let n = n.trim().trim_matches('"').parse::<usize>()
+                       .map_err(|_| anyhow!("Invalid problem number on line {}", line_no))?;
+                let dataset = dataset.trim().trim_matches('"');
+                anyhow::ensure!(!dataset.is_empty(), "Missing dataset name on line {}", line_no);
+                problem = Some((n, dataset.to_owned()));
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')
+                              .ok_or_else(|| anyhow!("Expected `key = value` on line {}", line_no))?;
+            let part = key.trim().strip_prefix("part")
+                      .and_then(|n| n.parse::<usize>().ok())
+                      .ok_or_else(|| anyhow!("Invalid key '{}' on line {}", key.trim(), line_no))?;
+            let (problem, dataset) = problem.clone()
+                                    .ok_or_else(|| anyhow!("Answer outside of a table on line {}",
+                                                           line_no))?;
+
+            let value = value.trim();
+            let value = if let Some(rest) = value.strip_prefix("'''") {
+                // multi-line literal string - a newline immediately after the opening delimiter is
+                // dropped, as in TOML
+                let mut text = String::from(rest);
+                while !text.contains("'''") {
+                    let (_, next) = lines.next()
+                                   .ok_or_else(|| anyhow!("Unterminated string on line {}",
+                                                          line_no))?;
+                    text.push('\n');
+                    text.push_str(next);
+                }
+                let end = text.find("'''").unwrap();
+                anyhow::ensure!(text[end+3..].trim().is_empty(),
+                                "Trailing characters after string on line {}", line_no);
+                text.truncate(end);
+                text.strip_prefix('\n').map(|s| s.to_owned()).unwrap_or(text)
+            } else if let Some(rest) = value.strip_prefix('\'') {
+                rest.strip_suffix('\'')
+                    .ok_or_else(|| anyhow!("Unterminated string on line {}", line_no))?
+                    .to_owned()
+            } else if let Some(rest) = value.strip_prefix('"') {
+                let rest = rest.strip_suffix('"')
+                          .ok_or_else(|| anyhow!("Unterminated string on line {}", line_no))?;
+                unescape(rest).ok_or_else(|| anyhow!("Invalid escape on line {}", line_no))?
+            } else {
+                value.parse::<i64>()
+                     .map_err(|_| anyhow!("Invalid value on line {}", line_no))?
+                     .to_string()
+            };
+
+            let key = (problem, dataset, part);
+            anyhow::ensure!(!entries.contains_key(&key),
+                            "Duplicate answer for {:02}p{} ({} dataset) on line {}",
+                            problem, part, key.1, line_no);
+            entries.insert(key, value);
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Get the stored answer for a given problem, dataset and part, if any
+    pub fn get(&self, problem: usize, dataset: &str, part: usize) -> Option<&str> {
+        self.entries.get(&(problem, dataset.to_owned(), part)).map(|s| s.as_str())
+    }
+
+    /// Compare an answer against the stored one
+    ///
+    /// See [`Answer::matches`] for how answers are compared.
+    pub fn check(&self, problem: usize, dataset: &str, part: usize, answer: &Answer) -> Verdict {
+        match self.get(problem, dataset, part) {
+            None => Verdict::Missing,
+            Some(expected) if answer.matches(expected) => Verdict::Pass,
+            Some(expected) => Verdict::Fail { expected: expected.to_owned() },
+        }
+    }
+}
+
+/// Process backslash escapes in a basic string
+fn unescape(s: &str) -> Option<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        out.push(match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            '\\' => '\\',
+            '"' => '"',
+            _ => return None,
+        });
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_values() {
+        let answers = Answers::parse(r#"
+# comment
+[01]
+part1 = 24000
+part2 = "45000"
+
+[5]
+part1 = 'CMZ'
+part2 = "a\"b"
+
+[10]
+part2 = '''
+#..#
+##.#
+'''
+"#).unwrap();
+
+        assert_eq!(answers.get(1, "real", 1), Some("24000"));
+        assert_eq!(answers.get(1, "real", 2), Some("45000"));
+        assert_eq!(answers.get(5, "real", 1), Some("CMZ"));
+        assert_eq!(answers.get(5, "real", 2), Some("a\"b"));
+        assert_eq!(answers.get(10, "real", 1), None);
+        assert_eq!(answers.get(10, "real", 2), Some("#..#\n##.#\n"));
+    }
+
+    #[test]
+    fn verdicts() {
+        let answers = Answers::parse("[10]\npart1 = 13140\npart2 = '''\n# #\n ##\n'''\n").unwrap();
+        let grid = crate::grid::Grid::from_data(vec![true, false, true, false, true, true], 3);
+
+        assert_eq!(answers.check(10, "real", 1, &13140.into()), Verdict::Pass);
+        assert_eq!(answers.check(10, "real", 1, &13141.into()),
+                   Verdict::Fail { expected: "13140".to_owned() });
+        assert_eq!(answers.check(10, "real", 2, &grid.into()), Verdict::Pass);
+        assert_eq!(answers.check(11, "real", 1, &10605.into()), Verdict::Missing);
+    }
+
+    #[test]
+    fn datasets() {
+        let answers = Answers::parse("[05]\npart1 = 'VJSFHWGFT'\n\
+                                      [05.example1]\npart1 = 'CMZ'\n\
+                                      [\"05\".\"team mate\"]\npart1 = 'TQRFCBSJJ'\n").unwrap();
+
+        assert_eq!(answers.get(5, "real", 1), Some("VJSFHWGFT"));
+        assert_eq!(answers.get(5, "example1", 1), Some("CMZ"));
+        assert_eq!(answers.get(5, "team mate", 1), Some("TQRFCBSJJ"));
+        assert_eq!(answers.check(5, "example2", 1, &"CMZ".into()), Verdict::Missing);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Answers::parse("part1 = 3").is_err());
+        assert!(Answers::parse("[01]\npart1 = \"x").is_err());
+        assert!(Answers::parse("[01]\nanswer = 3").is_err());
+        assert!(Answers::parse("[01]\npart1 = 3\npart1 = 4").is_err());
+
+        let err = Answers::parse("[05.example1]\npart1 = 'CMZ'\n\n[05.example1]\npart1 = 'CMZ'\n");
+        assert_eq!(err.unwrap_err().to_string(),
+                   "Duplicate answer for 05p1 (example1 dataset) on line 5");
+    }
+}
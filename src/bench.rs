@@ -0,0 +1,273 @@
+use anyhow::{anyhow, Result};
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use aoc2022::solution::{ParamValues, Problem};
+
+use crate::inputs::Inputs;
+
+/// Settings for a benchmark run
+pub struct Config {
+    /// Number of timed repetitions of each stage
+    pub runs: usize,
+
+    /// Number of untimed repetitions before measurement starts
+    pub warmup: usize,
+
+    /// File to write the results to, for use as a later baseline
+    pub save: Option<PathBuf>,
+
+    /// Baseline file to compare results against
+    pub compare: Option<PathBuf>,
+
+    /// Slowdown, in percent of the baseline median, above which a stage is flagged
+    pub threshold: f64,
+
+    /// Implementation to time for parts which have it, rather than the default
+    pub variant: Option<String>,
+
+    /// Where to read the inputs from
+    pub inputs: Inputs,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            runs: 10,
+            warmup: 2,
+            save: None,
+            compare: None,
+            threshold: 10.0,
+            variant: None,
+            inputs: Inputs::default(),
+        }
+    }
+}
+
+/// A separately-timed stage of solving a problem
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum Stage {
+    Load,
+    Part1,
+    Part2,
+}
+
+impl Stage {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Load  => "load",
+            Self::Part1 => "part1",
+            Self::Part2 => "part2",
+        }
+    }
+
+    fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "load"  => Some(Self::Load),
+            "part1" => Some(Self::Part1),
+            "part2" => Some(Self::Part2),
+            _ => None,
+        }
+    }
+}
+
+/// Summary statistics over the repetitions of one stage, in nanoseconds
+#[derive(Copy, Clone, Debug)]
+struct Stats {
+    min: f64,
+    median: f64,
+    mean: f64,
+    stddev: f64,
+}
+
+impl Stats {
+    fn from_samples(mut samples: Vec<f64>) -> Self {
+        assert!(!samples.is_empty());
+        samples.sort_unstable_by(|a, b| a.total_cmp(b));
+
+        let n = samples.len();
+        let median = if n.is_multiple_of(2) {
+            (samples[n/2 - 1] + samples[n/2]) / 2.0
+        } else {
+            samples[n/2]
+        };
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let var = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n as f64;
+
+        Self { min: samples[0], median, mean, stddev: var.sqrt() }
+    }
+}
+
+/// Time a function over the configured number of warmup and measured runs
+fn measure<T, F: FnMut() -> T>(cfg: &Config, mut func: F) -> Stats {
+    for _ in 0..cfg.warmup {
+        std::hint::black_box((func)());
+    }
+
+    let samples = (0..cfg.runs.max(1))
+                 .map(|_| {
+                     let begin = Instant::now();
+                     std::hint::black_box((func)());
+                     begin.elapsed().as_nanos() as f64
+                 })
+                 .collect();
+
+    Stats::from_samples(samples)
+}
+
+/// Render a duration in nanoseconds using a sensible unit
+fn fmt_nanos(ns: f64) -> String {
+    if ns >= 1e9 {
+        format!("{:.2} s", ns / 1e9)
+    } else if ns >= 1e6 {
+        format!("{:.2} ms", ns / 1e6)
+    } else if ns >= 1e3 {
+        format!("{:.2} µs", ns / 1e3)
+    } else {
+        format!("{:.0} ns", ns)
+    }
+}
+
+type Results = Vec<((usize, Stage), Stats)>;
+
+/// Load a baseline previously written by [`save_baseline`]
+fn load_baseline(path: &std::path::Path) -> Result<HashMap<(usize, Stage), Stats>> {
+    let data = std::fs::read_to_string(path)
+              .map_err(|e| anyhow!("Unable to read {}: {}", path.display(), e))?;
+
+    let mut out = HashMap::new();
+    for (idx, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let [prob, stage, min, median, mean, stddev] = fields[..] else {
+            anyhow::bail!("Invalid baseline entry on line {}", idx + 1);
+        };
+        let stage = Stage::from_name(stage)
+                   .ok_or_else(|| anyhow!("Invalid stage '{}' on line {}", stage, idx + 1))?;
+        out.insert((prob.parse()?, stage), Stats {
+            min: min.parse()?,
+            median: median.parse()?,
+            mean: mean.parse()?,
+            stddev: stddev.parse()?,
+        });
+    }
+
+    Ok(out)
+}
+
+/// Write results in a form that can later be loaded as a baseline
+fn save_baseline(path: &std::path::Path, results: &Results) -> Result<()> {
+    use std::fmt::Write;
+
+    let mut out = String::from("# problem stage min median mean stddev (nanoseconds)\n");
+    for ((prob, stage), s) in results {
+        writeln!(out, "{:02} {} {:.0} {:.0} {:.0} {:.0}",
+                 prob, stage.name(), s.min, s.median, s.mean, s.stddev)?;
+    }
+
+    std::fs::write(path, out).map_err(|e| anyhow!("Unable to write {}: {}", path.display(), e))
+}
+
+/// Benchmark each stage of the given problems
+///
+/// Problems are given along with the parts to time. Each problem's input is read into memory up
+/// front, so that `load_input` timings don't include file IO. Returns `true` if any stage
+/// regressed relative to the baseline.
+pub fn run(
+    cfg: &Config,
+    problems: &[(usize, &dyn Problem, Vec<usize>)],
+    params: &ParamValues,
+) -> Result<bool> {
+    let baseline = cfg.compare.as_deref().map(load_baseline).transpose()?;
+
+    println!("{:<4} {:<6} {:>10} {:>10} {:>10} {:>10} {:>14}",
+             "prob", "stage", "min", "median", "mean", "stddev",
+             if baseline.is_some() { "vs base" } else { "" });
+
+    let mut results = Vec::new();
+    let mut regressed = false;
+    for (p_num, prob, parts) in problems.iter() {
+        let (p_num, prob) = (*p_num, *prob);
+        let data = match cfg.inputs.read(p_num) {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!("{:02}: {}", p_num, e);
+                continue;
+            }
+        };
+        let load = || prob.load(&mut std::io::Cursor::new(&data), params);
+
+        let input = match load() {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!("{:02}: Failed to load input: {}", p_num, e);
+                continue;
+            }
+        };
+
+        let mut stages = vec![(Stage::Load, measure(cfg, load))];
+        for (part, stage) in [(1, Stage::Part1), (2, Stage::Part2)] {
+            if !parts.contains(&part) {
+                continue;
+            }
+            let variant = prob.pick_variant(part, cfg.variant.as_deref());
+            if let Err(e) = input.solve_variant(part, variant) {
+                eprintln!("{:02}: {} failed: {}", p_num, stage.name(), e);
+                continue;
+            }
+            stages.push((stage, measure(cfg, || input.solve_variant(part, variant))));
+        }
+
+        for (stage, s) in stages {
+            let cmp = match baseline.as_ref().and_then(|b| b.get(&(p_num, stage))) {
+                Some(base) => {
+                    let delta = 100.0 * (s.median - base.median) / base.median;
+                    if delta > cfg.threshold {
+                        regressed = true;
+                        format!("{:+.1}% SLOWER", delta)
+                    } else {
+                        format!("{:+.1}%", delta)
+                    }
+                }
+                None if baseline.is_some() => "new".to_owned(),
+                None => String::new(),
+            };
+
+            println!("{:<4} {:<6} {:>10} {:>10} {:>10} {:>10} {:>14}",
+                     format!("{:02}", p_num), stage.name(),
+                     fmt_nanos(s.min), fmt_nanos(s.median), fmt_nanos(s.mean), fmt_nanos(s.stddev),
+                     cmp);
+            results.push(((p_num, stage), s));
+        }
+    }
+
+    if let Some(path) = cfg.save.as_deref() {
+        save_baseline(path, &results)?;
+        println!("Saved baseline to {}", path.display());
+    }
+
+    Ok(regressed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let s = Stats::from_samples(vec![4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(s.min, 1.0);
+        assert_eq!(s.median, 2.5);
+        assert_eq!(s.mean, 2.5);
+        assert!((s.stddev - 1.25f64.sqrt()).abs() < 1e-9);
+
+        let s = Stats::from_samples(vec![5.0, 1.0, 3.0]);
+        assert_eq!(s.median, 3.0);
+    }
+}
@@ -0,0 +1,236 @@
+use anyhow::Result;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aoc2022::answer::Answer;
+use aoc2022::gen::Rng;
+use aoc2022::solution::{Loaded, ParamValues, Problem};
+
+use crate::guard;
+
+/// Settings for a fuzzing run
+pub struct Config {
+    /// Seed for the first input, or `None` to pick one from the clock
+    ///
+    /// Input `i` for each problem is generated from `seed + i`, so any single input can be
+    /// regenerated with `--seed` and `--runs 1`.
+    pub seed: Option<u64>,
+
+    /// Number of inputs to try for each problem
+    pub runs: usize,
+
+    /// Rough size of the generated inputs
+    pub scale: usize,
+
+    /// Time limit for each stage
+    pub timeout: Duration,
+
+    /// Directory to save shrunk failing inputs in
+    pub out: PathBuf,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            runs: 10,
+            scale: 10,
+            timeout: Duration::from_secs(10),
+            out: PathBuf::from("fuzz"),
+        }
+    }
+}
+
+/// Upper limit on the inputs tried while shrinking a failure
+const MAX_SHRINK_ATTEMPTS: usize = 500;
+
+/// The way a stage went wrong
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Kind {
+    Error,
+    Panic,
+    Timeout,
+}
+
+impl Kind {
+    fn of(err: &anyhow::Error) -> Self {
+        if err.is::<guard::Panicked>() {
+            Self::Panic
+        } else if err.is::<guard::TimedOut>() {
+            Self::Timeout
+        } else {
+            Self::Error
+        }
+    }
+}
+
+/// What went wrong with an input
+///
+/// Shrinking only keeps smaller inputs which fail in the same way.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Failure {
+    Load(Kind),
+    Solve { part: usize, variant: &'static str, kind: Kind },
+    Disagree { part: usize },
+}
+
+impl Failure {
+    fn kind(self) -> Option<Kind> {
+        match self {
+            Self::Load(kind) | Self::Solve { kind, .. } => Some(kind),
+            Self::Disagree { .. } => None,
+        }
+    }
+}
+
+/// Load an input and solve each part with every implementation, returning the first failure
+fn check(
+    prob: &'static dyn Problem,
+    input: &str,
+    parts: &[usize],
+    params: &ParamValues,
+    timeout: Duration,
+) -> Option<(Failure, String)> {
+    let (data, params) = (input.to_owned(), params.clone());
+    let loaded: Arc<dyn Loaded> = match guard::run_guarded(Some(timeout), move || {
+        prob.load(&mut data.as_bytes(), &params)
+    }) {
+        Ok(x) => x.into(),
+        Err(e) => return Some((Failure::Load(Kind::of(&e)), format!("Failed to load input: {}", e))),
+    };
+
+    for part in parts.iter().cloned() {
+        let mut answers: Vec<(&str, Answer)> = Vec::new();
+        for variant in prob.variants(part) {
+            let loaded = loaded.clone();
+            match guard::run_guarded(Some(timeout), move || loaded.solve_variant(part, variant)) {
+                Ok(answer) => answers.push((variant, answer)),
+                Err(e) => {
+                    let kind = Kind::of(&e);
+                    return Some((Failure::Solve { part, variant, kind },
+                                 format!("Part {} ({}) failed: {}", part, variant, e)));
+                }
+            }
+        }
+
+        if answers.iter().any(|(_, a)| *a != answers[0].1) {
+            let answers = answers.iter()
+                         .map(|(v, a)| format!("{} = {}", v, a.to_string().trim()))
+                         .collect::<Vec<_>>();
+            return Some((Failure::Disagree { part },
+                         format!("Part {} variants disagree: {}", part, answers.join(", "))));
+        }
+    }
+
+    None
+}
+
+/// Shrink a failing input by removing as many lines, then characters, as possible
+///
+/// This is a simple form of delta debugging: chunks of the input are removed, starting with
+/// halves and working down to single units, keeping each removal which still fails the same way.
+fn shrink(input: &str, still_fails: impl Fn(&str) -> bool) -> String {
+    fn pass<'a>(
+        units: Vec<&'a str>,
+        join: &str,
+        attempts: &mut usize,
+        still_fails: &impl Fn(&str) -> bool,
+    ) -> Vec<&'a str> {
+        let mut units = units;
+        let mut chunk = (units.len() / 2).max(1);
+        loop {
+            let mut removed = false;
+            let mut start = 0;
+            while start < units.len() && *attempts < MAX_SHRINK_ATTEMPTS {
+                let end = (start + chunk).min(units.len());
+                let candidate = [&units[..start], &units[end..]].concat();
+                *attempts += 1;
+                if !candidate.is_empty() && still_fails(&(candidate.join(join) + join)) {
+                    units = candidate;
+                    removed = true;
+                } else {
+                    start = end;
+                }
+            }
+
+            if *attempts >= MAX_SHRINK_ATTEMPTS || (chunk == 1 && !removed) {
+                return units;
+            }
+            if !removed {
+                chunk /= 2;
+            }
+        }
+    }
+
+    let mut attempts = 0;
+    let lines = pass(input.lines().collect(), "\n", &mut attempts, &still_fails);
+    let input = lines.join("\n") + "\n";
+
+    // then character by character
+    let chars = input.split_inclusive(|_| true).collect::<Vec<_>>();
+    let chars = pass(chars, "", &mut attempts, &still_fails);
+    chars.concat()
+}
+
+/// Run generated inputs through every implementation of the selected problems and parts
+///
+/// Failures are reported as they're found, and shrunk inputs reproducing them are saved to the
+/// output directory. Returns whether there were any failures.
+pub fn run(
+    cfg: &Config,
+    problems: &[(usize, &'static dyn Problem, Vec<usize>)],
+    params: &ParamValues,
+) -> Result<bool> {
+    let seed = cfg.seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+    println!("Fuzzing with seed {}, scale {}", seed, cfg.scale);
+
+    let mut failed = false;
+    'problems: for (p_num, prob, parts) in problems.iter() {
+        let (p_num, prob) = (*p_num, *prob);
+        let mut failures = 0;
+        for case_seed in (0..cfg.runs).map(|i| seed.wrapping_add(i as u64)) {
+            let mut rng = Rng::new(case_seed);
+            let Some(input) = prob.generate(&mut rng, cfg.scale, params)? else {
+                println!("{:02}: No input generator", p_num);
+                continue 'problems;
+            };
+
+            let Some((failure, desc)) = check(prob, &input, parts, params, cfg.timeout) else {
+                continue;
+            };
+            failures += 1;
+            println!("{:02}: Seed {}: {}", p_num, case_seed, desc);
+            let lines = input.lines().count();
+
+            // each attempt would run into the time limit
+            let shrunk = if failure.kind() == Some(Kind::Timeout) {
+                input
+            } else {
+                shrink(&input, |s| {
+                    check(prob, s, parts, params, cfg.timeout).map(|f| f.0) == Some(failure)
+                })
+            };
+
+            std::fs::create_dir_all(&cfg.out)?;
+            let path = cfg.out.join(format!("{:02}-{}.txt", p_num, case_seed));
+            std::fs::write(&path, &shrunk)?;
+            println!("    Saved {} ({} of {} lines)", path.display(), shrunk.lines().count(), lines);
+        }
+
+        if failures > 0 {
+            failed = true;
+            println!("{:02}: {} of {} inputs failed", p_num, failures, cfg.runs);
+        } else {
+            println!("{:02}: {} inputs passed", p_num, cfg.runs);
+        }
+    }
+
+    Ok(failed)
+}
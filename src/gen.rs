@@ -0,0 +1,97 @@
+/// Small, fast pseudo-random number generator for building puzzle inputs
+///
+/// This is SplitMix64, which is more than good enough for generating test data. The same seed
+/// always produces the same sequence, so generated inputs can be reproduced from their seed.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `[0, n)`, which must not be empty
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Empty range");
+        // the bias from this is far too small to matter here
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A number in an inclusive range
+    pub fn range(&mut self, range: std::ops::RangeInclusive<i64>) -> i64 {
+        let (lo, hi) = range.into_inner();
+        assert!(lo <= hi, "Empty range");
+        let span = (hi as i128 - lo as i128 + 1) as u128;
+        (lo as i128 + (self.next_u64() as u128 % span) as i128) as i64
+    }
+
+    /// A count in an inclusive range
+    pub fn count(&mut self, range: std::ops::RangeInclusive<usize>) -> usize {
+        self.range(*range.start() as i64..=*range.end() as i64) as usize
+    }
+
+    /// Return `true` with the given probability
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    /// A random element of a slice, which must not be empty
+    pub fn pick<'a, T>(&mut self, xs: &'a [T]) -> &'a T {
+        &xs[self.below(xs.len())]
+    }
+
+    /// Shuffle a slice in place
+    pub fn shuffle<T>(&mut self, xs: &mut [T]) {
+        for i in (1..xs.len()).rev() {
+            xs.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// A random lowercase string of the given length
+    pub fn word(&mut self, len: usize) -> String {
+        (0..len).map(|_| (b'a' + self.below(26) as u8) as char).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deterministic() {
+        let a = (0..5).map(|_| Rng::new(7).next_u64()).collect::<Vec<_>>();
+        assert!(a.iter().all(|x| *x == a[0]));
+
+        let mut rng = Rng::new(7);
+        let b = (0..5).map(|_| rng.next_u64()).collect::<Vec<_>>();
+        assert_ne!(b[0], b[1]);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            assert!(rng.below(3) < 3);
+            assert!((-2..=2).contains(&rng.range(-2..=2)));
+            assert!((4..=6).contains(&rng.count(4..=6)));
+        }
+        assert_eq!(rng.range(i64::MIN..=i64::MIN), i64::MIN);
+        assert!(!rng.chance(0.0));
+        assert!(rng.chance(1.0));
+
+        let mut xs = (0..10).collect::<Vec<_>>();
+        rng.shuffle(&mut xs);
+        xs.sort();
+        assert_eq!(xs, (0..10).collect::<Vec<_>>());
+    }
+}
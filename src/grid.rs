@@ -1,5 +1,16 @@
 #![allow(dead_code)]
 
+mod bits;
+mod grid3;
+mod infinite;
+pub mod search;
+mod topology;
+
+pub use bits::BitGrid;
+pub use grid3::Grid3;
+pub use infinite::InfiniteGrid;
+pub use topology::{Bounded, Dir, Stitched, Topology, Toroidal, WrapVoid};
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Grid<T> {
     data: Vec<T>,
@@ -15,7 +26,7 @@ impl<T> Grid<T> {
     /// # Panics
     /// This will panic if `data.len()` is not divisible by `width`.
     pub fn from_data(data: Vec<T>, width: usize) -> Self {
-        assert!(data.len() % width == 0, "Data array is not evenly divisible into a grid");
+        assert!(data.len().is_multiple_of(width), "Data array is not evenly divisible into a grid");
         Self {
             height: data.len() / width,
             data, width,
@@ -104,20 +115,19 @@ impl<T> Grid<T> {
     }
 
     /// Iterate over each point on the grid in an unspecified order
-    pub fn points(&self) -> impl Iterator<Item=GridPoint<T>> {
-        (0..self.data.len()).into_iter()
-                            .map(|idx| GridPoint {
-                                index: idx,
-                                coords: (idx % self.width, idx / self.width),
-                                grid: self,
-                            })
+    pub fn points(&self) -> impl Iterator<Item=GridPoint<'_, T>> {
+        (0..self.data.len()).map(|idx| GridPoint {
+            index: idx,
+            coords: (idx % self.width, idx / self.width),
+            grid: self,
+        })
     }
 
     /// Get a reference to a specific point on the grid
     ///
     /// # Panics
     /// Panics if the given position is not inside the grid
-    pub fn point(&self, pos: (usize, usize)) -> GridPoint<T> {
+    pub fn point(&self, pos: (usize, usize)) -> GridPoint<'_, T> {
         assert!(pos.0 < self.width && pos.1 < self.height,
                 "Attempted to access position ({}, {}) outside grid", pos.0, pos.1);
         GridPoint {
@@ -132,7 +142,7 @@ impl<T> Grid<T> {
     /// # Panics
     /// Panics if the given row is not inside the grid.
     #[inline]
-    pub fn row_iter(&self, row: usize) -> impl Iterator<Item=&T> + DoubleEndedIterator + ExactSizeIterator {
+    pub fn row_iter(&self, row: usize) -> impl DoubleEndedIterator<Item=&T> + ExactSizeIterator {
         assert!(row < self.height, "Attempted to access row outside the grid");
 
         let start_idx = row*self.width;
@@ -144,7 +154,7 @@ impl<T> Grid<T> {
     /// # Panics
     /// Panics if the given row is not inside the grid.
     #[inline]
-    pub fn row_iter_mut(&mut self, row: usize) -> impl Iterator<Item=&mut T> + DoubleEndedIterator + ExactSizeIterator {
+    pub fn row_iter_mut(&mut self, row: usize) -> impl DoubleEndedIterator<Item=&mut T> + ExactSizeIterator {
         assert!(row < self.height, "Attempted to access row outside the grid");
 
         let start_idx = row*self.width;
@@ -156,7 +166,7 @@ impl<T> Grid<T> {
     /// # Panics
     /// Panics if the given column is not inside the grid.
     #[inline]
-    pub fn col_iter(&self, col: usize) -> impl Iterator<Item=&T> + DoubleEndedIterator + ExactSizeIterator {
+    pub fn col_iter(&self, col: usize) -> impl DoubleEndedIterator<Item=&T> + ExactSizeIterator {
         assert!(col < self.width, "Attempted to access column outside the grid");
 
         let start_idx = col;
@@ -171,7 +181,7 @@ impl<T> Grid<T> {
     /// # Panics
     /// Panics if the given column is not inside the grid.
     #[inline]
-    pub fn col_iter_mut(&mut self, col: usize) -> impl Iterator<Item=&mut T> + DoubleEndedIterator + ExactSizeIterator {
+    pub fn col_iter_mut(&mut self, col: usize) -> impl DoubleEndedIterator<Item=&mut T> + ExactSizeIterator {
         assert!(col < self.width, "Attempted to access column outside the grid");
 
         let start_idx = col;
@@ -183,13 +193,17 @@ impl<T> Grid<T> {
 
     /// Display the grid to the console using a given rendering function
     pub fn show_with<F: Fn(&T) -> char>(&self, func: F) {
-        eprintln!();
+        eprint!("\n{}", self.render_with(func));
+    }
+
+    /// Draw the grid as text using a given rendering function, with a newline after each row
+    pub fn render_with<F: Fn(&T) -> char>(&self, func: F) -> String {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
         for row in self.data.chunks(self.width) {
-            for x in row {
-                eprint!("{}", (func)(x));
-            }
-            eprintln!();
+            out.extend(row.iter().map(&func));
+            out.push('\n');
         }
+        out
     }
 }
 
@@ -228,7 +242,7 @@ impl<T: Copy> Grid<T> {
 
 impl<T: PartialEq<T> + Eq> Grid<T> {
     /// Iterate over grid cells with a given value
-    pub fn find(&self, val: T) -> impl Iterator<Item=GridPoint<T>> {
+    pub fn find(&self, val: T) -> impl Iterator<Item=GridPoint<'_, T>> {
         use std::ops::Deref;
 
         self.points().filter(move |p| p.deref() == &val)
@@ -257,11 +271,7 @@ pub struct GridPoint<'grid, T> {
 
 impl<T> Clone for GridPoint<'_, T> {
     fn clone(&self) -> Self {
-        Self {
-            index: self.index,
-            coords: self.coords,
-            grid: self.grid,
-        }
+        *self
     }
 }
 impl<T> Copy for GridPoint<'_, T> {}
@@ -403,6 +413,13 @@ impl<'g, T> GridPoint<'g, T> {
         }
     }
 
+    /// Take a single step in a direction, with the topology deciding what happens at the edges
+    ///
+    /// This returns the point landed on and the direction then being faced.
+    pub fn step<Top: Topology<T>>(&self, topology: &Top, dir: Dir) -> Option<(Self, Dir)> {
+        topology.step(*self, dir)
+    }
+
     /// Iterate over neighboring cells
     pub fn neighbors<'a>(&'a self) -> impl Iterator<Item=GridPoint<'g, T>> + 'a {
         [(-1,-1), (0, -1), (1, -1),
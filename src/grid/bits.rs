@@ -0,0 +1,420 @@
+use super::Grid;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A grid of booleans, packed into a bit per cell
+///
+/// Each row is stored as a run of words, with X coordinate `x` in bit `x % 64` of word `x / 64`.
+/// Bits past the end of a row are always kept clear, so whole words can be compared and counted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitGrid {
+    words: Vec<u64>,
+    width: usize,
+    height: usize,
+
+    /// Number of words in each row
+    stride: usize,
+}
+
+impl BitGrid {
+    /// Create a new grid with every cell clear
+    pub fn new(width: usize, height: usize) -> Self {
+        let stride = width.div_ceil(BITS);
+        Self { words: vec![0; stride * height], width, height, stride }
+    }
+
+    /// Construct a grid by calling a function with each coordinate
+    pub fn from_fn<F: Fn(usize, usize) -> bool>(width: usize, height: usize, func: F) -> Self {
+        let mut grid = Self::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                if func(x, y) {
+                    grid.words[y * grid.stride + x / BITS] |= 1 << (x % BITS);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Pack a grid of booleans
+    pub fn from_grid(grid: &Grid<bool>) -> Self {
+        Self::from_fn(grid.width(), grid.height(), |x, y| *grid.get((x, y)))
+    }
+
+    /// Unpack into a grid of booleans
+    pub fn to_grid(&self) -> Grid<bool> {
+        Grid::from_fn(self.width, self.height, |x, y| self.get((x, y)))
+    }
+
+    /// Get the width of the grid
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the height of the grid
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Word index and bit mask for a position
+    ///
+    /// # Panics
+    /// Panics if the given position is not inside the grid.
+    #[inline]
+    fn bit(&self, pos: (usize, usize)) -> (usize, u64) {
+        assert!(pos.0 < self.width && pos.1 < self.height,
+                "Attempted to access position ({}, {}) outside grid", pos.0, pos.1);
+
+        (pos.1 * self.stride + pos.0 / BITS, 1 << (pos.0 % BITS))
+    }
+
+    /// Get the value at given coordinates
+    ///
+    /// # Panics
+    /// Panics if the given position is not inside the grid.
+    #[inline]
+    pub fn get(&self, pos: (usize, usize)) -> bool {
+        let (idx, mask) = self.bit(pos);
+        self.words[idx] & mask != 0
+    }
+
+    /// Set the value at given coordinates
+    ///
+    /// # Panics
+    /// Panics if the given position is not inside the grid.
+    #[inline]
+    pub fn set(&mut self, pos: (usize, usize), val: bool) {
+        let (idx, mask) = self.bit(pos);
+        if val {
+            self.words[idx] |= mask;
+        } else {
+            self.words[idx] &= !mask;
+        }
+    }
+
+    /// Set every cell to a given value
+    pub fn fill(&mut self, val: bool) {
+        self.words.fill(if val { !0 } else { 0 });
+        if val {
+            self.clear_padding();
+        }
+    }
+
+    /// Count the set cells
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Iterate over the coordinates of the set cells, row by row
+    pub fn ones(&self) -> impl Iterator<Item=(usize, usize)> + '_ {
+        self.words.iter().enumerate().flat_map(move |(i, word)| {
+            let (y, x0) = (i / self.stride, i % self.stride * BITS);
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                let bit = word.trailing_zeros() as usize;
+                word &= word.wrapping_sub(1);
+                (bit < BITS).then_some((x0 + bit, y))
+            })
+        })
+    }
+
+    /// Mask of the bits in the last word of each row which are inside the grid
+    fn last_mask(&self) -> u64 {
+        match self.width % BITS {
+            0 => !0,
+            n => (1 << n) - 1,
+        }
+    }
+
+    fn clear_padding(&mut self) {
+        if self.stride > 0 {
+            let mask = self.last_mask();
+            for row in self.words.chunks_mut(self.stride) {
+                row[row.len() - 1] &= mask;
+            }
+        }
+    }
+
+    /// Move every cell `n` places towards higher X coordinates, clearing the cells left behind
+    pub fn shift_right(&mut self, n: usize) {
+        let mask = self.last_mask();
+        for row in self.words.chunks_mut(self.stride.max(1)) {
+            shift_up(row, n);
+            if let Some(last) = row.last_mut() {
+                *last &= mask;
+            }
+        }
+    }
+
+    /// Move every cell `n` places towards lower X coordinates, clearing the cells left behind
+    pub fn shift_left(&mut self, n: usize) {
+        for row in self.words.chunks_mut(self.stride.max(1)) {
+            shift_down(row, n);
+        }
+    }
+
+    /// Move every cell `n` places towards higher X coordinates, wrapping around within its row
+    pub fn rotate_right(&mut self, n: usize) {
+        if self.width == 0 {
+            return;
+        }
+        let n = n % self.width;
+        let mask = self.last_mask();
+
+        let mut wrapped = vec![0; self.stride];
+        for row in self.words.chunks_mut(self.stride) {
+            wrapped.copy_from_slice(row);
+            shift_down(&mut wrapped, self.width - n);
+            shift_up(row, n);
+            for (word, w) in row.iter_mut().zip(&wrapped) {
+                *word |= w;
+            }
+            row[self.stride - 1] &= mask;
+        }
+    }
+
+    /// Move every cell `n` places towards lower X coordinates, wrapping around within its row
+    pub fn rotate_left(&mut self, n: usize) {
+        if self.width > 0 {
+            self.rotate_right(self.width - n % self.width);
+        }
+    }
+
+    /// Move every row `n` places towards higher Y coordinates, wrapping around
+    pub fn rotate_down(&mut self, n: usize) {
+        if self.height > 0 {
+            self.words.rotate_right(n % self.height * self.stride);
+        }
+    }
+
+    /// Move every row `n` places towards lower Y coordinates, wrapping around
+    pub fn rotate_up(&mut self, n: usize) {
+        if self.height > 0 {
+            self.words.rotate_left(n % self.height * self.stride);
+        }
+    }
+
+    /// Count how many of each cell's eight neighbors are set
+    ///
+    /// The neighbors are lined up with each cell by shifting whole rows, then added together a
+    /// word at a time, with each bit of the counts kept in a separate word.
+    pub fn neighbor_counts(&self) -> Grid<u8> {
+        if self.height == 0 {
+            return Grid::filled(self.width, 0, 0);
+        }
+
+        let mut right = self.clone();
+        right.shift_right(1);
+        let mut left = self.clone();
+        left.shift_left(1);
+
+        // the rows above and below each cell, moved onto it
+        let mut neighbors = Vec::new();
+        for row in [&left, self, &right] {
+            let mut from_above = row.clone();
+            from_above.rotate_down(1);
+            from_above.words[..self.stride].fill(0);
+
+            let mut from_below = row.clone();
+            from_below.rotate_up(1);
+            let last = from_below.words.len() - self.stride;
+            from_below.words[last..].fill(0);
+
+            neighbors.push(from_above);
+            neighbors.push(from_below);
+        }
+        neighbors.push(left);
+        neighbors.push(right);
+
+        // ripple-carry adders, with bit `i` of each count in plane `i`
+        let mut planes = vec![[0u64; 4]; self.words.len()];
+        for grid in &neighbors {
+            for (sum, word) in planes.iter_mut().zip(&grid.words) {
+                let mut carry = *word;
+                for plane in sum.iter_mut() {
+                    (*plane, carry) = (*plane ^ carry, *plane & carry);
+                }
+            }
+        }
+
+        Grid::from_fn(self.width, self.height, |x, y| {
+            let sum = planes[y * self.stride + x / BITS];
+            (0..4).map(|i| (((sum[i] >> (x % BITS)) & 1) as u8) << i).sum()
+        })
+    }
+
+    /// Find the cells with at least one of their eight neighbors set
+    ///
+    /// This works on whole words at a time, so it's much faster than counting the neighbors.
+    pub fn any_neighbors(&self) -> BitGrid {
+        // spread each row sideways, then combine it with the rows above and below
+        let mut spread = self.clone();
+        let mut right = self.clone();
+        right.shift_right(1);
+        let mut left = self.clone();
+        left.shift_left(1);
+        spread |= &right;
+        spread |= &left;
+
+        let mut out = &right | &left;
+        for y in 0..self.height {
+            let row = y * self.stride..(y + 1) * self.stride;
+            if y > 0 {
+                let above = (y - 1) * self.stride;
+                for (i, word) in out.words[row.clone()].iter_mut().enumerate() {
+                    *word |= spread.words[above + i];
+                }
+            }
+            if y + 1 < self.height {
+                let below = (y + 1) * self.stride;
+                for (i, word) in out.words[row].iter_mut().enumerate() {
+                    *word |= spread.words[below + i];
+                }
+            }
+        }
+        out
+    }
+
+    fn zip_with<F: Fn(u64, u64) -> u64>(&mut self, other: &BitGrid, func: F) {
+        assert!(self.width == other.width && self.height == other.height,
+                "Attempted to combine grids of different sizes");
+
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a = func(*a, *b);
+        }
+    }
+}
+
+/// Shift the bits of a multi-word value `n` places towards the high end
+fn shift_up(words: &mut [u64], n: usize) {
+    let (skip, bits) = (n / BITS, n % BITS);
+    for i in (0..words.len()).rev() {
+        let lo = i.checked_sub(skip).map_or(0, |j| words[j]);
+        let carry = match (bits, i.checked_sub(skip + 1)) {
+            (0, _) | (_, None) => 0,
+            (_, Some(j)) => words[j] >> (BITS - bits),
+        };
+        words[i] = lo << bits | carry;
+    }
+}
+
+/// Shift the bits of a multi-word value `n` places towards the low end
+fn shift_down(words: &mut [u64], n: usize) {
+    let (skip, bits) = (n / BITS, n % BITS);
+    for i in 0..words.len() {
+        let hi = words.get(i + skip).copied().unwrap_or(0);
+        let carry = match (bits, words.get(i + skip + 1)) {
+            (0, _) | (_, None) => 0,
+            (_, Some(w)) => w << (BITS - bits),
+        };
+        words[i] = hi >> bits | carry;
+    }
+}
+
+macro_rules! bit_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:tt) => {
+        impl std::ops::$assign_trait<&BitGrid> for BitGrid {
+            fn $assign_method(&mut self, other: &BitGrid) {
+                self.zip_with(other, |a, b| a $op b);
+            }
+        }
+
+        impl std::ops::$trait for &BitGrid {
+            type Output = BitGrid;
+
+            fn $method(self, other: &BitGrid) -> BitGrid {
+                let mut out = self.clone();
+                out.zip_with(other, |a, b| a $op b);
+                out
+            }
+        }
+    };
+}
+
+bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+bit_op!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+impl std::ops::Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> BitGrid {
+        let mut out = self.clone();
+        for word in out.words.iter_mut() {
+            *word = !*word;
+        }
+        out.clear_padding();
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cells(grid: &BitGrid) -> String {
+        grid.to_grid().render_with(|c| if *c { '#' } else { '.' })
+    }
+
+    #[test]
+    fn shifts() {
+        // wide enough to cross a word boundary
+        let mut grid = BitGrid::from_fn(70, 2, |x, y| x == 0 || (y == 1 && x >= 62));
+        assert_eq!(grid.count_ones(), 10);
+        assert_eq!(BitGrid::from_grid(&grid.to_grid()), grid);
+
+        grid.rotate_right(5);
+        let expected = BitGrid::from_fn(70, 2, |x, y| x == 5 || (y == 1 && (x >= 67 || x <= 5)));
+        assert_eq!(cells(&grid), cells(&expected));
+        grid.rotate_left(5);
+        grid.rotate_left(65);
+        let expected = BitGrid::from_fn(70, 2, |x, y| x == 5 || (y == 1 && x <= 5) || (y == 1 && x >= 67));
+        assert_eq!(cells(&grid), cells(&expected));
+
+        grid.shift_right(66);
+        assert_eq!(grid.ones().collect::<Vec<_>>(), [(66, 1), (67, 1), (68, 1), (69, 1)]);
+        grid.shift_left(69);
+        grid.rotate_up(1);
+        assert_eq!(grid.ones().collect::<Vec<_>>(), [(0, 0)]);
+        grid.rotate_down(3);
+        assert_eq!(grid.ones().collect::<Vec<_>>(), [(0, 1)]);
+    }
+
+    #[test]
+    fn combining() {
+        let a = BitGrid::from_fn(67, 3, |x, _| x % 2 == 0);
+        let b = BitGrid::from_fn(67, 3, |_, y| y == 1);
+        assert_eq!((&a & &b).count_ones(), 34);
+        assert_eq!((&a | &b).count_ones(), 34 * 3 + 33);
+        assert_eq!((&a ^ &b).count_ones(), 34 * 2 + 33);
+        assert_eq!((!&a).count_ones(), 33 * 3);
+
+        let mut c = a.clone();
+        c ^= &a;
+        assert_eq!(c, BitGrid::new(67, 3));
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = BitGrid::from_fn(66, 4, |x, y| (x, y) == (1, 1) || (x, y) == (64, 2) || (x, y) == (65, 2));
+        let counts = grid.neighbor_counts();
+        assert_eq!((*counts.get((0, 0)), *counts.get((1, 1)), *counts.get((64, 3))), (1, 0, 2));
+        assert_eq!(counts.cells().map(|c| *c as usize).sum::<usize>(), 8 + 8 + 5);
+
+        let any = grid.any_neighbors();
+        assert_eq!(any, BitGrid::from_fn(66, 4, |x, y| *counts.get((x, y)) > 0));
+
+        // every possible count, across word boundaries
+        let grid = BitGrid::from_fn(130, 5, |x, y| (x * 7 + y * 3) % 5 != 0 || y == 2);
+        let counts = grid.neighbor_counts();
+        for point in counts.points() {
+            let (x, y) = point.coords();
+            let expected = (y.saturating_sub(1)..(y + 2).min(5))
+                          .flat_map(|ny| (x.saturating_sub(1)..(x + 2).min(130)).map(move |nx| (nx, ny)))
+                          .filter(|n| *n != (x, y) && grid.get(*n))
+                          .count();
+            assert_eq!(*point as usize, expected, "at {:?}", (x, y));
+        }
+        assert_eq!(counts.cells().max(), Some(&8));
+    }
+}
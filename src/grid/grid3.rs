@@ -0,0 +1,247 @@
+/// Offsets to the six cells sharing a face with a cell
+const FACES: [(isize, isize, isize); 6] = [
+    (-1, 0, 0), (1, 0, 0), (0, -1, 0), (0, 1, 0), (0, 0, -1), (0, 0, 1),
+];
+
+/// A dense three-dimensional grid, with X, Y and Z coordinates starting from 0
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid3<T> {
+    data: Vec<T>,
+    width: usize,
+    height: usize,
+    depth: usize,
+}
+
+impl<T> Grid3<T> {
+    /// Construct a grid by calling a function with each coordinate
+    pub fn from_fn<F: Fn(usize, usize, usize) -> T>(
+        width: usize,
+        height: usize,
+        depth: usize,
+        func: F,
+    ) -> Self {
+        let mut data = Vec::with_capacity(width * height * depth);
+        for z in 0..depth {
+            for y in 0..height {
+                for x in 0..width {
+                    data.push((func)(x, y, z));
+                }
+            }
+        }
+
+        Self { data, width, height, depth }
+    }
+
+    /// Map the individual cell values through a function, returning a new grid
+    pub fn map<U, F: Fn(&T) -> U>(self, func: F) -> Grid3<U> {
+        let data = self.data.iter().map(func).collect();
+        Grid3 { data, width: self.width, height: self.height, depth: self.depth }
+    }
+
+    /// Get the size of the grid along the X axis
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the size of the grid along the Y axis
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the size of the grid along the Z axis
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    #[inline]
+    fn index(&self, (x, y, z): (usize, usize, usize)) -> Option<usize> {
+        (x < self.width && y < self.height && z < self.depth)
+            .then(|| (z * self.height + y) * self.width + x)
+    }
+
+    /// Get the value at given coordinates
+    ///
+    /// # Panics
+    /// Panics if the given position is not inside the grid.
+    #[inline]
+    pub fn get(&self, pos: (usize, usize, usize)) -> &T {
+        self.try_get(pos).unwrap_or_else(|| {
+            panic!("Attempted to access position ({}, {}, {}) outside grid", pos.0, pos.1, pos.2)
+        })
+    }
+
+    /// Try to get the value at given coordinates
+    #[inline]
+    pub fn try_get(&self, pos: (usize, usize, usize)) -> Option<&T> {
+        self.index(pos).map(|i| &self.data[i])
+    }
+
+    /// Get mutable reference to the value at given coordinates
+    ///
+    /// # Panics
+    /// Panics if the given position is not inside the grid.
+    #[inline]
+    pub fn get_mut(&mut self, pos: (usize, usize, usize)) -> &mut T {
+        let Some(i) = self.index(pos) else {
+            panic!("Attempted to access position ({}, {}, {}) outside grid", pos.0, pos.1, pos.2)
+        };
+        &mut self.data[i]
+    }
+
+    /// Set the value at given coordinates
+    ///
+    /// # Panics
+    /// Panics if the given position is not inside the grid.
+    #[inline]
+    pub fn set(&mut self, pos: (usize, usize, usize), val: T) {
+        *self.get_mut(pos) = val;
+    }
+
+    /// Iterate over all cells in unspecified order
+    pub fn cells(&self) -> impl Iterator<Item=&T> {
+        self.data.iter()
+    }
+
+    /// Iterate over the coordinates and value of each cell in an unspecified order
+    pub fn points(&self) -> impl Iterator<Item=((usize, usize, usize), &T)> {
+        let (w, h) = (self.width, self.height);
+        self.data.iter().enumerate().map(move |(i, c)| ((i % w, i / w % h, i / (w * h)), c))
+    }
+
+    /// Get the cell at a given offset from some coordinates, if it's inside the grid
+    pub fn offset(&self, (x, y, z): (usize, usize, usize), (dx, dy, dz): (isize, isize, isize))
+        -> Option<(usize, usize, usize)>
+    {
+        let pos = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?, z.checked_add_signed(dz)?);
+        self.index(pos).map(|_| pos)
+    }
+
+    /// Iterate over the (up to six) cells sharing a face with a cell
+    pub fn faces(&self, pos: (usize, usize, usize))
+        -> impl Iterator<Item=(usize, usize, usize)> + '_
+    {
+        FACES.into_iter().filter_map(move |d| self.offset(pos, d))
+    }
+
+    /// Iterate over the (up to 26) cells sharing a face, edge or corner with a cell
+    pub fn neighbors(&self, pos: (usize, usize, usize))
+        -> impl Iterator<Item=(usize, usize, usize)> + '_
+    {
+        (-1..=1).flat_map(|dz| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dx| (dx, dy, dz))))
+                .filter(|d| *d != (0, 0, 0))
+                .filter_map(move |d| self.offset(pos, d))
+    }
+
+    /// Find every cell reachable from a starting cell by moving between the faces of cells which
+    /// pass a predicate
+    ///
+    /// Returns a grid of the same size which is `true` for the reachable cells. Nothing is
+    /// reachable if the starting cell doesn't pass the predicate itself.
+    pub fn flood_fill<F: Fn(&T) -> bool>(&self, start: (usize, usize, usize), passable: F)
+        -> Grid3<bool>
+    {
+        let mut reached = Grid3::filled(self.width, self.height, self.depth, false);
+        if !passable(self.get(start)) {
+            return reached;
+        }
+
+        reached.set(start, true);
+        let mut stack = vec![start];
+        while let Some(pos) = stack.pop() {
+            for next in self.faces(pos) {
+                if !*reached.get(next) && passable(self.get(next)) {
+                    reached.set(next, true);
+                    stack.push(next);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Count the faces of solid cells which don't touch another solid cell
+    ///
+    /// Faces on the outside of the grid count as exposed.
+    pub fn surface_area<F: Fn(&T) -> bool>(&self, solid: F) -> usize {
+        self.points()
+            .filter(|(_, c)| solid(c))
+            .map(|(pos, _)| FACES.iter().filter(|d| {
+                self.offset(pos, **d).is_none_or(|next| !solid(self.get(next)))
+            }).count())
+            .sum()
+    }
+
+    /// Count the faces of solid cells which can be reached from outside the grid
+    ///
+    /// Unlike [`Grid3::surface_area`], this doesn't count the faces around pockets which are
+    /// completely enclosed by solid cells.
+    pub fn exterior_surface_area<F: Fn(&T) -> bool>(&self, solid: F) -> usize {
+        // a layer of space all around connects every part of the outside
+        let padded = Grid3::from_fn(self.width + 2, self.height + 2, self.depth + 2, |x, y, z| {
+            let inner = (x.wrapping_sub(1), y.wrapping_sub(1), z.wrapping_sub(1));
+            self.try_get(inner).is_some_and(&solid)
+        });
+        let outside = padded.flood_fill((0, 0, 0), |c| !*c);
+
+        padded.points()
+              .filter(|(_, c)| **c)
+              .map(|(pos, _)| padded.faces(pos).filter(|next| *outside.get(*next)).count())
+              .sum()
+    }
+}
+
+impl<T: Copy> Grid3<T> {
+    /// Create a new grid filled with a given value
+    pub fn filled(width: usize, height: usize, depth: usize, data: T) -> Self {
+        Self { data: vec![data; width * height * depth], width, height, depth }
+    }
+
+    /// Pad the grid with a given value in every direction
+    ///
+    /// This will return a new grid that adds `n` copies of `value` to each side.
+    pub fn padded(&self, val: T, n: usize) -> Self {
+        Self::from_fn(self.width + 2 * n, self.height + 2 * n, self.depth + 2 * n, |x, y, z| {
+            let inner = (x.wrapping_sub(n), y.wrapping_sub(n), z.wrapping_sub(n));
+            self.try_get(inner).copied().unwrap_or(val)
+        })
+    }
+
+    /// Set every cell to a given value
+    pub fn fill(&mut self, data: T) {
+        self.data.fill(data);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn neighborhoods() {
+        let grid = Grid3::from_fn(3, 4, 5, |x, y, z| (x, y, z));
+        assert!(grid.points().all(|(pos, c)| pos == *c));
+
+        assert_eq!(grid.faces((1, 1, 1)).count(), 6);
+        assert_eq!(grid.faces((0, 0, 0)).collect::<Vec<_>>(), [(1, 0, 0), (0, 1, 0), (0, 0, 1)]);
+        assert_eq!(grid.neighbors((1, 1, 1)).count(), 26);
+        assert_eq!(grid.neighbors((2, 3, 4)).count(), 7);
+
+        let padded = grid.padded((9, 9, 9), 1);
+        assert_eq!((padded.width(), padded.height(), padded.depth()), (5, 6, 7));
+        assert_eq!((*padded.get((0, 0, 0)), *padded.get((1, 1, 1))), ((9, 9, 9), (0, 0, 0)));
+    }
+
+    #[test]
+    fn surfaces() {
+        // a hollow 3x3x3 cube, with one cell sticking out
+        let mut grid = Grid3::from_fn(4, 3, 3, |x, _, _| x < 3);
+        grid.set((1, 1, 1), false);
+        grid.set((3, 1, 1), true);
+
+        assert_eq!(grid.surface_area(|c| *c), 54 + 4 + 6);
+        assert_eq!(grid.exterior_surface_area(|c| *c), 54 + 4);
+
+        let inside = grid.flood_fill((1, 1, 1), |c| !*c);
+        assert_eq!(inside.cells().filter(|c| **c).count(), 1);
+        assert_eq!(grid.flood_fill((0, 0, 0), |c| !*c).cells().filter(|c| **c).count(), 0);
+    }
+}
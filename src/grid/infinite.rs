@@ -0,0 +1,208 @@
+use super::Grid;
+
+/// Width and height of each chunk of cells
+const CHUNK: isize = 16;
+
+/// A grid without fixed bounds, indexed by signed coordinates
+///
+/// Every cell starts out with a default value, and cells are only stored once something is
+/// written near them, in square chunks. The chunks are found through a directory covering every
+/// chunk written so far, which grows as needed, so that lookups don't need to hash anything. The
+/// grid keeps track of the bounding box of the cells which have been written, which is what gets
+/// iterated over and drawn.
+#[derive(Clone, Debug)]
+pub struct InfiniteGrid<T> {
+    /// Row-major directory of chunks, which are allocated when first written
+    chunks: Vec<Option<Box<[T]>>>,
+
+    /// Chunk coordinates of the top-left corner of the directory
+    origin: (isize, isize),
+
+    /// Width and height of the directory, in chunks
+    size: (usize, usize),
+
+    default: T,
+
+    /// Top-left and bottom-right corners of the written cells, inclusive
+    bounds: Option<((isize, isize), (isize, isize))>,
+}
+
+/// Split coordinates into the chunk containing them and the index within that chunk
+#[inline]
+fn split((x, y): (isize, isize)) -> ((isize, isize), usize) {
+    let chunk = (x.div_euclid(CHUNK), y.div_euclid(CHUNK));
+    (chunk, (y.rem_euclid(CHUNK) * CHUNK + x.rem_euclid(CHUNK)) as usize)
+}
+
+impl<T: Clone> InfiniteGrid<T> {
+    /// Create an empty grid, where every cell has the given value
+    pub fn new(default: T) -> Self {
+        Self { chunks: Vec::new(), origin: (0, 0), size: (0, 0), default, bounds: None }
+    }
+
+    /// Position of a chunk in the directory, if it's covered
+    #[inline]
+    fn slot(&self, (cx, cy): (isize, isize)) -> Option<usize> {
+        let (x, y) = ((cx - self.origin.0) as usize, (cy - self.origin.1) as usize);
+        // negative offsets wrap around to huge ones, so this checks both ends
+        (x < self.size.0 && y < self.size.1).then(|| y * self.size.0 + x)
+    }
+
+    /// Grow the directory to cover a chunk, leaving some room to grow further
+    fn cover(&mut self, (cx, cy): (isize, isize)) {
+        let (w, h) = (self.size.0 as isize, self.size.1 as isize);
+        let (x0, y0) = if self.chunks.is_empty() { (cx, cy) } else { self.origin };
+        let (x1, y1) = if self.chunks.is_empty() { (cx + 1, cy + 1) } else { (x0 + w, y0 + h) };
+
+        // double the size on whichever sides need to grow
+        let grow = |lo: isize, hi: isize, c: isize| {
+            let span = (hi - lo).max(1);
+            (if c < lo { c - span } else { lo },
+             if c >= hi { c + 1 + span } else { hi })
+        };
+        let (x0, x1) = grow(x0, x1, cx);
+        let (y0, y1) = grow(y0, y1, cy);
+
+        let size = ((x1 - x0) as usize, (y1 - y0) as usize);
+        let mut chunks = Vec::with_capacity(size.0 * size.1);
+        chunks.resize_with(size.0 * size.1, || None);
+        for (i, chunk) in std::mem::take(&mut self.chunks).into_iter().enumerate() {
+            let (x, y) = (self.origin.0 + (i % self.size.0) as isize,
+                          self.origin.1 + (i / self.size.0) as isize);
+            chunks[(y - y0) as usize * size.0 + (x - x0) as usize] = chunk;
+        }
+
+        self.chunks = chunks;
+        self.origin = (x0, y0);
+        self.size = size;
+    }
+
+    /// Get the value at given coordinates
+    #[inline]
+    pub fn get(&self, pos: (isize, isize)) -> &T {
+        let (chunk, idx) = split(pos);
+        match self.slot(chunk).and_then(|s| self.chunks[s].as_ref()) {
+            Some(c) => &c[idx],
+            None => &self.default,
+        }
+    }
+
+    /// Get mutable reference to the value at given coordinates
+    ///
+    /// The cell counts as written, and is included in the bounding box from now on.
+    pub fn get_mut(&mut self, pos: (isize, isize)) -> &mut T {
+        self.bounds = Some(match self.bounds {
+            None => (pos, pos),
+            Some((min, max)) => ((min.0.min(pos.0), min.1.min(pos.1)),
+                                 (max.0.max(pos.0), max.1.max(pos.1))),
+        });
+
+        let (chunk, idx) = split(pos);
+        let slot = match self.slot(chunk) {
+            Some(s) => s,
+            None => {
+                self.cover(chunk);
+                self.slot(chunk).unwrap()
+            }
+        };
+        let default = &self.default;
+        let chunk = self.chunks[slot]
+                   .get_or_insert_with(|| vec![default.clone(); (CHUNK * CHUNK) as usize].into());
+        &mut chunk[idx]
+    }
+
+    /// Set the value at given coordinates
+    pub fn set(&mut self, pos: (isize, isize), val: T) {
+        *self.get_mut(pos) = val;
+    }
+
+    /// Top-left and bottom-right corners of the cells written so far, inclusive
+    pub fn bounds(&self) -> Option<((isize, isize), (isize, isize))> {
+        self.bounds
+    }
+
+    /// Get the width of the bounding box
+    pub fn width(&self) -> usize {
+        self.bounds.map_or(0, |(min, max)| (max.0 - min.0 + 1) as usize)
+    }
+
+    /// Get the height of the bounding box
+    pub fn height(&self) -> usize {
+        self.bounds.map_or(0, |(min, max)| (max.1 - min.1 + 1) as usize)
+    }
+
+    /// Iterate over each point in the bounding box, in row-major order
+    pub fn points(&self) -> impl Iterator<Item=((isize, isize), &T)> {
+        let ((x0, y0), (x1, y1)) = self.bounds.unwrap_or(((0, 0), (-1, -1)));
+        (y0..=y1).flat_map(move |y| (x0..=x1).map(move |x| ((x, y), self.get((x, y)))))
+    }
+
+    /// Iterate over the eight cells around some coordinates
+    pub fn neighbors(&self, (x, y): (isize, isize)) -> impl Iterator<Item=((isize, isize), &T)> {
+        [(-1,-1), (0, -1), (1, -1),
+         (-1,0),           (1, 0),
+         (-1,1),  (0, 1),  (1, 1)].into_iter().map(move |(dx, dy)| {
+            let pos = (x + dx, y + dy);
+            (pos, self.get(pos))
+        })
+    }
+
+    /// Copy the bounding box into a fixed grid, returning it with the position of its top-left
+    /// corner
+    pub fn to_grid(&self) -> ((isize, isize), Grid<T>) {
+        let (min, _) = self.bounds.unwrap_or_default();
+        let grid = Grid::from_fn(self.width(), self.height(), |x, y| {
+            self.get((min.0 + x as isize, min.1 + y as isize)).clone()
+        });
+        (min, grid)
+    }
+
+    /// Display the bounding box to the console using a given rendering function
+    pub fn show_with<F: Fn(&T) -> char>(&self, func: F) {
+        eprint!("\n{}", self.render_with(func));
+    }
+
+    /// Draw the bounding box as text using a given rendering function, with a newline after
+    /// each row
+    pub fn render_with<F: Fn(&T) -> char>(&self, func: F) -> String {
+        let mut out = String::with_capacity((self.width() + 1) * self.height());
+        let width = self.width();
+        for (i, (_, cell)) in self.points().enumerate() {
+            out.push(func(cell));
+            if (i + 1) % width == 0 {
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn growing() {
+        let mut grid = InfiniteGrid::new('.');
+        assert_eq!((grid.bounds(), grid.render_with(|c| *c)), (None, String::new()));
+
+        grid.set((-17, 3), '#');
+        grid.set((2, -1), '@');
+        assert_eq!(grid.bounds(), Some(((-17, -1), (2, 3))));
+        assert_eq!((grid.width(), grid.height()), (20, 5));
+        assert_eq!((*grid.get((-17, 3)), *grid.get((2, -1)), *grid.get((1000, -1000))), ('#', '@', '.'));
+        assert_eq!(grid.neighbors((1, 0)).filter(|(_, c)| **c == '@').count(), 1);
+
+        let (origin, fixed) = grid.to_grid();
+        assert_eq!(origin, (-17, -1));
+        assert_eq!((*fixed.get((19, 0)), *fixed.get((0, 4))), ('@', '#'));
+        assert_eq!(grid.render_with(|c| *c).lines().nth(4), Some("#..................."));
+
+        // growing the directory keeps everything in place
+        for i in -100..100 {
+            grid.set((i * 7, i * -3), 'o');
+        }
+        assert_eq!((*grid.get((-17, 3)), *grid.get((2, -1)), *grid.get((-700, 300))), ('#', '@', 'o'));
+        assert_eq!(grid.points().filter(|(_, c)| **c == 'o').count(), 200);
+    }
+}
@@ -0,0 +1,224 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use super::{Grid, GridPoint};
+
+/// Distances found by searching a grid, and the route taken to each cell
+///
+/// Every search moves between cells sharing an edge. If several starting cells are given, each
+/// cell's distance is from the nearest of them.
+#[derive(Clone, Debug)]
+pub struct Paths {
+    dist: Grid<usize>,
+    prev: Grid<Option<(usize, usize)>>,
+    goal: Option<(usize, usize)>,
+}
+
+impl Paths {
+    fn new<T>(grid: &Grid<T>) -> Self {
+        Self {
+            dist: Grid::filled(grid.width(), grid.height(), usize::MAX),
+            prev: Grid::filled(grid.width(), grid.height(), None),
+            goal: None,
+        }
+    }
+
+    /// Distance to a cell, if it was reached
+    ///
+    /// When a search stops early at a goal, only the distances up to the goal's are final.
+    pub fn distance(&self, pos: (usize, usize)) -> Option<usize> {
+        Some(*self.dist.get(pos)).filter(|d| *d != usize::MAX)
+    }
+
+    /// Distance to every cell, or `None` for the cells which weren't reached
+    pub fn distances(&self) -> Grid<Option<usize>> {
+        self.dist.clone().map(|d| Some(*d).filter(|d| *d != usize::MAX))
+    }
+
+    /// The goal cell the search stopped at, if any
+    pub fn goal(&self) -> Option<(usize, usize)> {
+        self.goal
+    }
+
+    /// Route to a cell, from the start it was reached from up to the cell itself
+    pub fn path(&self, pos: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        self.distance(pos)?;
+
+        let mut path = vec![pos];
+        while let Some(prev) = *self.prev.get(*path.last().unwrap()) {
+            path.push(prev);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Cells sharing an edge with a point
+fn adjacent<'g, T>(point: GridPoint<'g, T>) -> impl Iterator<Item=GridPoint<'g, T>> {
+    [point.left(), point.right(), point.up(), point.down()].into_iter().flatten()
+}
+
+/// Breadth-first search, where every move takes a single step
+///
+/// `passable` is called with the cell being moved from and the cell being moved to. The search
+/// stops at the first cell found which is a `goal`, so pass `|_| false` to reach everything.
+pub fn bfs<T, P, G>(
+    grid: &Grid<T>,
+    starts: impl IntoIterator<Item=(usize, usize)>,
+    passable: P,
+    goal: G,
+) -> Paths
+where
+    P: Fn(GridPoint<'_, T>, GridPoint<'_, T>) -> bool,
+    G: Fn(GridPoint<'_, T>) -> bool,
+{
+    let mut paths = Paths::new(grid);
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if *paths.dist.get(start) != 0 {
+            paths.dist.set(start, 0);
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(pos) = queue.pop_front() {
+        let point = grid.point(pos);
+        if goal(point) {
+            paths.goal = Some(pos);
+            break;
+        }
+
+        let next_dist = *paths.dist.get(pos) + 1;
+        for next in adjacent(point) {
+            if *paths.dist.get(next.coords()) == usize::MAX && passable(point, next) {
+                paths.dist.set(next.coords(), next_dist);
+                paths.prev.set(next.coords(), Some(pos));
+                queue.push_back(next.coords());
+            }
+        }
+    }
+
+    paths
+}
+
+/// Dijkstra's algorithm, for moves with varying costs
+///
+/// `cost` is called with the cell being moved from and the cell being moved to, and returns
+/// `None` if the move isn't possible. The search stops at the first cell found which is a
+/// `goal`, so pass `|_| false` to reach everything.
+pub fn dijkstra<T, C, G>(
+    grid: &Grid<T>,
+    starts: impl IntoIterator<Item=(usize, usize)>,
+    cost: C,
+    goal: G,
+) -> Paths
+where
+    C: Fn(GridPoint<'_, T>, GridPoint<'_, T>) -> Option<usize>,
+    G: Fn(GridPoint<'_, T>) -> bool,
+{
+    astar(grid, starts, cost, |_| 0, goal)
+}
+
+/// A* search, which is Dijkstra's algorithm guided towards the goal by an estimate of the cost
+/// remaining from each cell
+///
+/// The distance found to the goal is only the shortest if `heuristic` never overestimates.
+pub fn astar<T, C, H, G>(
+    grid: &Grid<T>,
+    starts: impl IntoIterator<Item=(usize, usize)>,
+    cost: C,
+    heuristic: H,
+    goal: G,
+) -> Paths
+where
+    C: Fn(GridPoint<'_, T>, GridPoint<'_, T>) -> Option<usize>,
+    H: Fn(GridPoint<'_, T>) -> usize,
+    G: Fn(GridPoint<'_, T>) -> bool,
+{
+    let mut paths = Paths::new(grid);
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        paths.dist.set(start, 0);
+        queue.push(Reverse((heuristic(grid.point(start)), 0, start)));
+    }
+
+    while let Some(Reverse((_, dist, pos))) = queue.pop() {
+        if dist > *paths.dist.get(pos) {
+            continue; // already found a better way here
+        }
+
+        let point = grid.point(pos);
+        if goal(point) {
+            paths.goal = Some(pos);
+            break;
+        }
+
+        for next in adjacent(point) {
+            let Some(step) = cost(point, next) else { continue };
+            let next_dist = dist + step;
+            if next_dist < *paths.dist.get(next.coords()) {
+                paths.dist.set(next.coords(), next_dist);
+                paths.prev.set(next.coords(), Some(pos));
+                queue.push(Reverse((next_dist + heuristic(next), next_dist, next.coords())));
+            }
+        }
+    }
+
+    paths
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MAZE: &str = "\
+S.9.G
+.###.
+..1..
+";
+
+    fn maze() -> Grid<char> {
+        let width = MAZE.find('\n').unwrap();
+        Grid::from_data(MAZE.chars().filter(|c| *c != '\n').collect(), width)
+    }
+
+    #[test]
+    fn searches() {
+        let grid = maze();
+        let open = |_: GridPoint<'_, char>, to: GridPoint<'_, char>| *to != '#';
+        let cost = |_: GridPoint<'_, char>, to: GridPoint<'_, char>| match *to {
+            '#' => None,
+            '0'..='9' => Some(to.to_digit(10).unwrap() as usize + 1),
+            _ => Some(1),
+        };
+        let is_goal = |p: GridPoint<'_, char>| *p == 'G';
+
+        let paths = bfs(&grid, [(0, 0)], open, is_goal);
+        assert_eq!(paths.goal(), Some((4, 0)));
+        assert_eq!(paths.distance((4, 0)), Some(4));
+        assert_eq!(paths.path((4, 0)).unwrap(), [(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]);
+
+        // going through the 9 is slower than going around, even through the 1
+        let paths = dijkstra(&grid, [(0, 0)], cost, is_goal);
+        assert_eq!(paths.distance((4, 0)), Some(9));
+        let path = paths.path((4, 0)).unwrap();
+        assert_eq!((path.len(), path.contains(&(2, 2))), (9, true));
+        assert!(path.windows(2).all(|w| w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1) == 1));
+
+        let manhattan = |p: GridPoint<'_, char>| 4 - p.coords().0 + p.coords().1;
+        let paths = astar(&grid, [(0, 0)], cost, manhattan, is_goal);
+        assert_eq!(paths.distance((4, 0)), Some(9));
+        assert_eq!(paths.path((4, 0)), Some(path));
+    }
+
+    #[test]
+    fn multiple_starts() {
+        let grid = maze();
+        let paths = bfs(&grid, [(0, 0), (4, 0)], |_, to| *to != '#', |_| false);
+        assert_eq!(paths.goal(), None);
+        assert_eq!(paths.distance((3, 2)), Some(3));
+        assert_eq!(paths.path((3, 2)).unwrap()[0], (4, 0));
+        assert_eq!(paths.distance((1, 1)), None);
+        assert_eq!(paths.distances().cells().filter(|d| d.is_some()).count(), 12);
+    }
+}
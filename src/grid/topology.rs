@@ -0,0 +1,193 @@
+use super::GridPoint;
+
+/// A direction of movement on a grid
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Dir { Left, Right, Up, Down }
+
+impl Dir {
+    pub const ALL: [Dir; 4] = [Dir::Left, Dir::Right, Dir::Up, Dir::Down];
+
+    /// Direction after a quarter turn anticlockwise
+    pub fn turn_left(self) -> Self {
+        match self {
+            Self::Left  => Self::Down,
+            Self::Right => Self::Up,
+            Self::Up    => Self::Left,
+            Self::Down  => Self::Right,
+        }
+    }
+
+    /// Direction after a quarter turn clockwise
+    pub fn turn_right(self) -> Self {
+        match self {
+            Self::Left  => Self::Up,
+            Self::Right => Self::Down,
+            Self::Up    => Self::Right,
+            Self::Down  => Self::Left,
+        }
+    }
+
+    /// The opposite direction
+    pub fn flip(self) -> Self {
+        match self {
+            Self::Up    => Self::Down,
+            Self::Down  => Self::Up,
+            Self::Left  => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    /// Change in coordinates for a single step, with Y increasing downwards
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Self::Left  => (-1, 0),
+            Self::Right => (1, 0),
+            Self::Up    => (0, -1),
+            Self::Down  => (0, 1),
+        }
+    }
+}
+
+/// How the cells of a grid connect to each other, and particularly what lies past its edges
+///
+/// A step may land facing a different way than it started, such as when crossing an edge of a
+/// cube which has been unfolded onto the grid.
+pub trait Topology<T> {
+    /// Take a single step from a point, returning where it lands and the direction it's then
+    /// facing, or `None` if there's nowhere to go
+    fn step<'g>(&self, point: GridPoint<'g, T>, dir: Dir) -> Option<(GridPoint<'g, T>, Dir)>;
+}
+
+/// Steps can't go past the edge of the grid
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Bounded;
+
+impl<T> Topology<T> for Bounded {
+    fn step<'g>(&self, point: GridPoint<'g, T>, dir: Dir) -> Option<(GridPoint<'g, T>, Dir)> {
+        let next = match dir {
+            Dir::Left  => point.left(),
+            Dir::Right => point.right(),
+            Dir::Up    => point.up(),
+            Dir::Down  => point.down(),
+        };
+        next.map(|p| (p, dir))
+    }
+}
+
+/// Steps off an edge of the grid come back on at the opposite edge
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Toroidal;
+
+impl<T> Topology<T> for Toroidal {
+    fn step<'g>(&self, point: GridPoint<'g, T>, dir: Dir) -> Option<(GridPoint<'g, T>, Dir)> {
+        let (w, h) = (point.grid.width, point.grid.height);
+        let (x, y) = point.coords;
+        let pos = match dir {
+            Dir::Left  => ((x + w - 1) % w, y),
+            Dir::Right => ((x + 1) % w, y),
+            Dir::Up    => (x, (y + h - 1) % h),
+            Dir::Down  => (x, (y + 1) % h),
+        };
+        Some((point.grid.point(pos), dir))
+    }
+}
+
+/// Some cells are void, and steps off the edge of the rest of the grid come back on at the
+/// opposite edge of the same row or column, skipping over any void
+pub struct WrapVoid<V> {
+    is_void: V,
+}
+
+impl<V> WrapVoid<V> {
+    pub fn new<T>(is_void: V) -> Self where V: Fn(&T) -> bool {
+        Self { is_void }
+    }
+}
+
+impl<T, V: Fn(&T) -> bool> Topology<T> for WrapVoid<V> {
+    fn step<'g>(&self, point: GridPoint<'g, T>, dir: Dir) -> Option<(GridPoint<'g, T>, Dir)> {
+        if let Some(next) = Bounded.step(point, dir).filter(|(p, _)| !(self.is_void)(p)) {
+            return Some(next);
+        }
+
+        let (x, y) = point.coords;
+        let mut next = point.grid.point(match dir {
+            Dir::Left  => (point.grid.width - 1, y),
+            Dir::Right => (0, y),
+            Dir::Up    => (x, point.grid.height - 1),
+            Dir::Down  => (x, 0),
+        });
+        while (self.is_void)(&next) {
+            next = Bounded.step(next, dir)?.0;
+        }
+        Some((next, dir))
+    }
+}
+
+/// Some cells are void, and a function decides where steps off the edge of the rest of the grid
+/// come back on
+///
+/// The function is given the point being left and the direction of the step, and returns the
+/// position and direction to continue with.
+pub struct Stitched<V, S> {
+    is_void: V,
+    stitch: S,
+}
+
+impl<V, S> Stitched<V, S> {
+    pub fn new<T>(is_void: V, stitch: S) -> Self
+    where
+        V: Fn(&T) -> bool,
+        S: Fn(GridPoint<'_, T>, Dir) -> Option<((usize, usize), Dir)>,
+    {
+        Self { is_void, stitch }
+    }
+}
+
+impl<T, V, S> Topology<T> for Stitched<V, S>
+where
+    V: Fn(&T) -> bool,
+    S: Fn(GridPoint<'_, T>, Dir) -> Option<((usize, usize), Dir)>,
+{
+    fn step<'g>(&self, point: GridPoint<'g, T>, dir: Dir) -> Option<(GridPoint<'g, T>, Dir)> {
+        if let Some(next) = Bounded.step(point, dir).filter(|(p, _)| !(self.is_void)(p)) {
+            return Some(next);
+        }
+
+        let (pos, dir) = (self.stitch)(point, dir)?;
+        Some((point.grid.point(pos), dir))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::Grid;
+
+    #[test]
+    fn topologies() {
+        let grid = Grid::from_data(" ab \n".chars().filter(|c| *c != '\n').collect(), 4);
+        let start = grid.point((2, 0));
+        let walk = |top: &dyn Topology<char>, n| {
+            let mut point = start;
+            (0..n).map(|_| {
+                point = top.step(point, Dir::Right)?.0;
+                Some(*point)
+            }).collect::<Option<String>>()
+        };
+
+        assert_eq!(walk(&Bounded, 1).as_deref(), Some(" "));
+        assert_eq!(walk(&Bounded, 2), None);
+        assert_eq!(walk(&Toroidal, 4).as_deref(), Some("  ab"));
+        assert_eq!(walk(&WrapVoid::new(|c: &char| *c == ' '), 3).as_deref(), Some("aba"));
+        assert_eq!(Toroidal.step(start, Dir::Up).map(|(p, d)| (p.coords(), d)), Some(((2, 0), Dir::Up)));
+
+        // going off the end of the row comes back on the row below, facing the other way
+        let grid = Grid::from_fn(3, 2, |x, y| x + 3 * y);
+        let snake = Stitched::new(|_: &usize| false, |p: GridPoint<'_, usize>, dir: Dir| {
+            Some(((p.coords().0, 1 - p.coords().1), dir.flip()))
+        });
+        let (point, dir) = grid.point((2, 0)).step(&snake, Dir::Right).unwrap();
+        assert_eq!((*point, dir), (5, Dir::Left));
+    }
+}
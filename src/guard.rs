@@ -0,0 +1,149 @@
+use anyhow::Result;
+
+use std::cell::RefCell;
+use std::time::Duration;
+
+thread_local! {
+    /// Whether the current thread is running guarded code
+    static GUARDED: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+
+    /// Description of the last panic caught on this thread
+    static LAST_PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Error from guarded code which panicked, with a description of the panic
+#[derive(Debug)]
+pub struct Panicked(pub String);
+
+impl std::fmt::Display for Panicked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Solver {}", self.0)
+    }
+}
+
+impl std::error::Error for Panicked {}
+
+/// Error from guarded code which didn't finish within its time limit
+#[derive(Debug)]
+pub struct TimedOut(pub Duration);
+
+impl std::fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Timed out after {:.1} s", self.0.as_secs_f64())
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Install a panic hook which records panics in guarded code rather than printing them
+///
+/// Panics outside of [`run_guarded`] are passed through to the previously installed hook.
+pub fn install_hook() {
+    let default = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if !GUARDED.with(|g| g.get()) {
+            (default)(info);
+            return;
+        }
+
+        let payload = info.payload();
+        let msg = payload.downcast_ref::<&str>().copied()
+                 .or_else(|| payload.downcast_ref::<String>().map(|s| s.as_str()))
+                 .unwrap_or("Box<dyn Any>");
+        let desc = match info.location() {
+            Some(loc) => format!("panicked at {}: {}", loc, msg),
+            None => format!("panicked: {}", msg),
+        };
+        LAST_PANIC.with(|p| *p.borrow_mut() = Some(desc));
+    }));
+}
+
+/// Run a function on this thread, converting any panic into a [`Panicked`] error
+///
+/// Calls can be nested, and the outer call stays guarded after an inner one returns.
+pub fn catch<T, F: FnOnce() -> Result<T>>(func: F) -> Result<T> {
+    let outer = GUARDED.with(|g| g.replace(true));
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(func));
+    GUARDED.with(|g| g.set(outer));
+
+    res.unwrap_or_else(|_| {
+        let desc = LAST_PANIC.with(|p| p.borrow_mut().take());
+        Err(Panicked(desc.unwrap_or_else(|| "panicked".to_owned())).into())
+    })
+}
+
+/// Run a function, converting panics and timeouts into errors
+///
+/// These are reported as [`Panicked`] and [`TimedOut`] errors respectively.
+///
+/// With a timeout, the function is run on its own thread. Rust threads can't be killed, so if the
+/// timeout expires the thread is abandoned and keeps running in the background until the process
+/// exits.
+pub fn run_guarded<T, F>(timeout: Option<Duration>, func: F) -> Result<T>
+where T: Send + 'static,
+      F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let Some(timeout) = timeout else {
+        return catch(func);
+    };
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::Builder::new()
+        .name("guarded".to_owned())
+        .spawn(move || {
+            // the receiver may be gone if we timed out - that's fine
+            let _ = tx.send(catch(func));
+        })?;
+
+    rx.recv_timeout(timeout)
+      .unwrap_or_else(|_| Err(TimedOut(timeout).into()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn panics_become_errors() {
+        install_hook();
+
+        let res = run_guarded::<(), _>(None, || panic!("oh no"));
+        let err = res.unwrap_err().to_string();
+        assert!(err.contains("oh no"), "{}", err);
+        assert!(err.contains("guard.rs"), "{}", err);
+        assert!(run_guarded::<(), _>(None, || panic!()).unwrap_err().is::<Panicked>());
+
+        let res = run_guarded(Some(Duration::from_secs(10)), || -> Result<()> {
+            let v: Vec<u8> = Vec::new();
+            let _ = v[3];
+            Ok(())
+        });
+        assert!(res.unwrap_err().to_string().contains("index out of bounds"));
+
+        assert_eq!(run_guarded(None, || Ok(3)).unwrap(), 3);
+    }
+
+    #[test]
+    fn nested() {
+        install_hook();
+
+        let res = catch(|| {
+            catch(|| Ok(()))?;
+            assert!(GUARDED.with(|g| g.get()));
+            catch::<(), _>(|| panic!("inner"))
+        });
+        assert!(res.unwrap_err().to_string().contains("inner"));
+        assert!(!GUARDED.with(|g| g.get()));
+    }
+
+    #[test]
+    fn timeouts() {
+        let res = run_guarded(Some(Duration::from_millis(10)), || {
+            std::thread::sleep(Duration::from_secs(5));
+            Ok(())
+        });
+        let err = res.unwrap_err();
+        assert!(err.to_string().contains("Timed out"));
+        assert!(err.is::<TimedOut>());
+    }
+}
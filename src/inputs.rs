@@ -0,0 +1,163 @@
+use anyhow::Result;
+
+use std::path::PathBuf;
+
+use aoc2022::answers::DEFAULT_DATASET;
+
+/// Default directory containing the inputs
+pub const DEFAULT_ROOT: &str = "inputs";
+
+/// Where to find each problem's inputs, and which of them to use
+///
+/// The inputs for problem 5 are under `<root>/05`. This is either a single input file, which is
+/// the `real` dataset, or a directory with a file for each named dataset, such as
+/// `<root>/05/real` and `<root>/05/example1`.
+#[derive(Clone, Debug)]
+pub struct Inputs {
+    pub root: PathBuf,
+
+    /// Dataset to read
+    pub dataset: String,
+}
+
+impl Default for Inputs {
+    fn default() -> Self {
+        Self { root: PathBuf::from(DEFAULT_ROOT), dataset: DEFAULT_DATASET.to_owned() }
+    }
+}
+
+impl Inputs {
+    /// The same inputs, but reading a different dataset
+    pub fn with_dataset(&self, dataset: &str) -> Self {
+        Self { root: self.root.clone(), dataset: dataset.to_owned() }
+    }
+
+    /// Location of the selected dataset for a problem
+    pub fn path(&self, p_num: usize) -> PathBuf {
+        let base = self.root.join(format!("{:02}", p_num));
+        if base.is_file() && self.dataset == DEFAULT_DATASET {
+            base
+        } else {
+            base.join(&self.dataset)
+        }
+    }
+
+    /// Default location of the expected-answers file, alongside the inputs
+    pub fn answers_path(&self) -> PathBuf {
+        self.root.join("answers.toml")
+    }
+
+    /// Names of every dataset a problem has, with the default one first
+    ///
+    /// Hidden files are ignored, as are any subdirectories.
+    pub fn datasets(&self, p_num: usize) -> Result<Vec<String>> {
+        let base = self.root.join(format!("{:02}", p_num));
+        if base.is_file() {
+            return Ok(vec![DEFAULT_DATASET.to_owned()]);
+        } else if !base.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&base)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with('.') && entry.file_type()?.is_file() {
+                names.push(name);
+            }
+        }
+        names.sort_by_key(|n| (n != DEFAULT_DATASET, n.clone()));
+        Ok(names)
+    }
+
+    /// Open the input for a problem
+    ///
+    /// If no path is given, this uses the selected dataset. The path `-` reads from stdin.
+    pub fn open(&self, p_num: usize, path: Option<&str>)
+        -> Result<Box<dyn std::io::BufRead + Send>>
+    {
+        let file = match path {
+            Some("-") => return Ok(Box::new(std::io::BufReader::new(std::io::stdin()))),
+            Some(name) => std::fs::File::open(name),
+            None => std::fs::File::open(self.path(p_num)),
+        };
+
+        let file = file.map_err(|e| anyhow::anyhow!("Failed to open input: {}", e))?;
+        Ok(Box::new(std::io::BufReader::new(file)))
+    }
+
+    /// Read the whole of a problem's input into memory
+    pub fn read(&self, p_num: usize) -> Result<Vec<u8>> {
+        std::fs::read(self.path(p_num)).map_err(|e| anyhow::anyhow!("Failed to open input: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A fresh directory of inputs, which is removed when dropped
+    struct TempRoot(PathBuf);
+
+    impl TempRoot {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("aoc2022-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn write(&self, path: &str) {
+            let path = self.0.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "input").unwrap();
+        }
+
+        fn inputs(&self) -> Inputs {
+            Inputs { root: self.0.clone(), ..Inputs::default() }
+        }
+    }
+
+    impl Drop for TempRoot {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn single_file() {
+        let root = TempRoot::new("single");
+        root.write("05");
+        let inputs = root.inputs();
+
+        assert_eq!(inputs.path(5), root.0.join("05"));
+        assert_eq!(inputs.with_dataset("example1").path(5), root.0.join("05/example1"));
+        assert_eq!(inputs.datasets(5).unwrap(), ["real"]);
+        assert_eq!(inputs.read(5).unwrap(), b"input");
+        assert!(inputs.with_dataset("example1").read(5).is_err());
+    }
+
+    #[test]
+    fn directory() {
+        let root = TempRoot::new("dir");
+        for name in ["example2", "real", "example1", ".hidden", "old/real"] {
+            root.write(&format!("05/{}", name));
+        }
+        let inputs = root.inputs();
+
+        assert_eq!(inputs.path(5), root.0.join("05/real"));
+        assert_eq!(inputs.with_dataset("example2").path(5), root.0.join("05/example2"));
+        assert_eq!(inputs.datasets(5).unwrap(), ["real", "example1", "example2"]);
+        assert_eq!(inputs.with_dataset("example1").read(5).unwrap(), b"input");
+    }
+
+    #[test]
+    fn missing() {
+        let root = TempRoot::new("missing");
+        let inputs = root.inputs();
+
+        assert_eq!(inputs.path(5), root.0.join("05/real"));
+        assert!(inputs.datasets(5).unwrap().is_empty());
+        assert!(inputs.read(5).is_err());
+    }
+}
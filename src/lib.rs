@@ -0,0 +1,291 @@
+pub mod answer;
+pub mod answers;
+pub mod gen;
+pub mod grid;
+pub mod ocr;
+pub mod parse;
+pub mod record;
+pub mod solution;
+pub mod util;
+
+/// Define a problem's [`Solution`](solution::Solution) from its loader and solver functions
+///
+/// Problems can declare named runtime parameters with defaults, which can be overridden with
+/// `--param name=value`. Solvers for such problems take the generated `Params` struct as a second
+/// argument:
+///
+/// ```ignore
+/// problem!(load_input => Vec<Reading> => (solve1 -> usize, solve2 -> usize),
+///          params { target_row: isize = 2_000_000 });
+/// ```
+///
+/// Parts can also have named alternative implementations, such as a slow but obviously correct
+/// reference, which take the same arguments as the main solvers. These can be selected with
+/// `--variant`, and are checked against each other by `--cross-check` and the example tests:
+///
+/// ```ignore
+/// problem!(load_input => Problem => (solve1 -> usize, solve2 -> usize),
+///          variants { part1: bfs = solve1_bfs, part2: bfs = solve2_bfs });
+/// ```
+///
+/// Parts whose answers can never be zero or negative, such as a count of steps which always has
+/// to be taken, can be listed so that any other answer is reported as a bug in the solver:
+///
+/// ```ignore
+/// problem!(load_input => Vec<Rock> => (solve1 -> usize, solve2 -> usize),
+///          positive { part1, part2 });
+/// ```
+///
+/// A generator can be given which produces random valid inputs, for fuzzing. It takes the random
+/// number generator, a rough size for the input, and the parameters if there are any:
+///
+/// ```ignore
+/// problem!(load_input => Vec<Motion> => (solve1 -> usize, solve2 -> usize),
+///          generator generate);
+/// ```
+///
+/// Finally, problems with intermediate state can give a function which starts stepping through a
+/// part, for the REPL. It takes the input, the parameters if there are any, and the part, and
+/// returns something implementing [`Inspect`](solution::Inspect):
+///
+/// ```ignore
+/// problem!(load_input => Problem => (solve1 -> String, solve2 -> String),
+///          generator generate, inspect inspect);
+/// ```
+///
+/// Simulations over 2D space can also give a function which runs a part while drawing frames to
+/// a [`Recorder`](record::Recorder), for `--record`. It takes the same arguments as the inspect
+/// function, followed by the recorder:
+///
+/// ```ignore
+/// problem!(load_input => Vec<Motion> => (solve1 -> usize, solve2 -> usize),
+///          generator generate, record record);
+/// ```
+macro_rules! problem {
+    ($load:path => $input:ty => $parts:tt
+     $(, params $params:tt)? $(, variants $variants:tt)? $(, positive $positive:tt)?
+     $(, generator $gen:path)? $(, inspect $inspect:path)? $(, record $record:path)?) => {
+        problem!(@parts $load => $input => $parts, [$($params)?], [$($variants)?],
+                 [$($positive)?], [$($gen)?], [$($inspect)?], [$($record)?]);
+    };
+    (@parts $load:path => $input:ty => (),
+     $params:tt, $variants:tt, $positive:tt, $gen:tt, $inspect:tt, $record:tt) => {
+        problem!(@impl $load => $input, 0, $params, $params, $variants, $positive,
+                 $gen, $inspect, $record,
+                 (none 1) -> crate::solution::NoAnswer,
+                 (none 2) -> crate::solution::NoAnswer);
+    };
+    (@parts $load:path => $input:ty => ($solve1:ident -> $ans1:ty),
+     $params:tt, $variants:tt, $positive:tt, $gen:tt, $inspect:tt, $record:tt) => {
+        problem!(@impl $load => $input, 1, $params, $params, $variants, $positive,
+                 $gen, $inspect, $record,
+                 (solver $solve1) -> $ans1,
+                 (none 2) -> crate::solution::NoAnswer);
+    };
+    (@parts $load:path => $input:ty => ($solve1:ident -> $ans1:ty, $solve2:ident -> $ans2:ty),
+     $params:tt, $variants:tt, $positive:tt, $gen:tt, $inspect:tt, $record:tt) => {
+        problem!(@impl $load => $input, 2, $params, $params, $variants, $positive,
+                 $gen, $inspect, $record,
+                 (solver $solve1) -> $ans1,
+                 (solver $solve2) -> $ans2);
+    };
+    (@part part1) => { 1 };
+    (@part part2) => { 2 };
+    (@call $mode:tt (none $part:literal), $input:ident, $params:ident) => {
+        anyhow::bail!("Problem has no part {}", $part)
+    };
+    (@call [] (solver $solve:ident), $input:ident, $params:ident) => {
+        $solve($input)
+    };
+    (@call [$p:tt] (solver $solve:ident), $input:ident, $params:ident) => {
+        $solve($input, $params)
+    };
+    (@generate $mode:tt [], $rng:ident, $scale:ident, $params:ident) => {
+        None
+    };
+    (@generate [] [$gen:path], $rng:ident, $scale:ident, $params:ident) => {
+        Some($gen($rng, $scale))
+    };
+    (@generate [$p:tt] [$gen:path], $rng:ident, $scale:ident, $params:ident) => {
+        Some($gen($rng, $scale, $params))
+    };
+    (@inspect $mode:tt [], $input:ident, $params:ident, $part:ident) => {
+        None
+    };
+    (@inspect [] [$inspect:path], $input:ident, $params:ident, $part:ident) => {
+        Some(Box::new($inspect($input, $part)))
+    };
+    (@inspect [$p:tt] [$inspect:path], $input:ident, $params:ident, $part:ident) => {
+        Some(Box::new($inspect($input, $params, $part)))
+    };
+    (@record $mode:tt [], $input:ident, $params:ident, $part:ident, $rec:ident) => {
+        None
+    };
+    (@record [] [$record:path], $input:ident, $params:ident, $part:ident, $rec:ident) => {
+        Some($record($input, $part, $rec))
+    };
+    (@record [$p:tt] [$record:path], $input:ident, $params:ident, $part:ident, $rec:ident) => {
+        Some($record($input, $params, $part, $rec))
+    };
+    (@impl $load:path => $input:ty, $parts:expr, $mode:tt,
+     [$({ $($(#[$meta:meta])* $name:ident: $pty:ty = $default:expr),* $(,)? })?],
+     [$({ $($vpart:ident: $vname:ident = $vsolve:ident),* $(,)? })?],
+     [$({ $($ppart:ident),* $(,)? })?],
+     $gen:tt, $inspect:tt, $record:tt,
+     $solve1:tt -> $ans1:ty, $solve2:tt -> $ans2:ty) => {
+        type Input = $input;
+
+        /// Runtime parameters for this day's puzzle
+        #[derive(Clone, Debug)]
+        pub struct Params {
+            $($(
+                $(#[$meta])*
+                pub $name: $pty,
+            )*)?
+        }
+
+        /// Solution for this day's puzzle
+        pub struct Solver;
+
+        impl crate::solution::Solution for Solver {
+            type Input = Input;
+            type Params = Params;
+            type Answer1 = $ans1;
+            type Answer2 = $ans2;
+
+            const PARTS: usize = $parts;
+
+            const PARAMS: &'static [crate::solution::ParamInfo] = &[
+                $($(crate::solution::ParamInfo { name: stringify!($name), default: stringify!($default) },)*)?
+            ];
+
+            const VARIANTS: &'static [crate::solution::VariantInfo] = &[
+                $($(crate::solution::VariantInfo { part: problem!(@part $vpart), name: stringify!($vname) },)*)?
+            ];
+
+            const POSITIVE: &'static [usize] = &[$($(problem!(@part $ppart),)*)?];
+
+            fn parse_params(
+                #[allow(unused_variables)] values: &crate::solution::ParamValues
+            ) -> anyhow::Result<Params> {
+                Ok(Params {
+                    $($($name: values.get(stringify!($name))?.unwrap_or($default),)*)?
+                })
+            }
+
+            fn parse(input: &mut dyn std::io::BufRead) -> anyhow::Result<Input> {
+                $load(input)
+            }
+
+            #[allow(unused_variables)]
+            fn part1(input: &Input, params: &Params) -> anyhow::Result<$ans1> {
+                problem!(@call $mode $solve1, input, params)
+            }
+
+            #[allow(unused_variables)]
+            fn part2(input: &Input, params: &Params) -> anyhow::Result<$ans2> {
+                problem!(@call $mode $solve2, input, params)
+            }
+
+            #[allow(unused_variables)]
+            fn solve_variant(input: &Input, params: &Params, part: usize, name: &str)
+                -> anyhow::Result<crate::answer::Answer>
+            {
+                $($(
+                    if part == problem!(@part $vpart) && name == stringify!($vname) {
+                        return problem!(@call $mode (solver $vsolve), input, params).map(Into::into);
+                    }
+                )*)?
+                anyhow::bail!("Part {} has no variant '{}'", part, name)
+            }
+
+            #[allow(unused_variables)]
+            fn generate(rng: &mut crate::gen::Rng, scale: usize, params: &Params) -> Option<String> {
+                problem!(@generate $mode $gen, rng, scale, params)
+            }
+
+            #[allow(unused_variables)]
+            fn inspect<'a>(input: &'a Input, params: &'a Params, part: usize)
+                -> Option<Box<dyn crate::solution::Inspect + 'a>>
+            {
+                problem!(@inspect $mode $inspect, input, params, part)
+            }
+
+            #[allow(unused_variables)]
+            fn record(input: &Input, params: &Params, part: usize,
+                      rec: &mut dyn crate::record::Recorder) -> Option<anyhow::Result<()>>
+            {
+                problem!(@record $mode $record, input, params, part, rec)
+            }
+        }
+    };
+}
+
+/// Generate tests checking a problem's answers for its worked examples
+///
+/// Each entry names a test module, the example input, any parameter values it needs, and the
+/// expected answer for some parts:
+///
+/// ```ignore
+/// examples! {
+///     small: EXAMPLE => part1 = 13, part2 = 1;
+///     large: LARGE_EXAMPLE, params(rounds = 10) => part2 = 36;
+/// }
+/// ```
+///
+/// Attributes such as `#[ignore]` can be given before a part, for examples which are too slow to
+/// run by default.
+macro_rules! examples {
+    {$(
+        $name:ident: $input:expr $(, params($($param:ident = $value:expr),*))? =>
+            $($(#[$attr:meta])* $part:ident = $answer:expr),+;
+    )*} => {
+        #[cfg(test)]
+        mod examples {
+            use super::*;
+
+            $(
+                mod $name {
+                    use super::*;
+
+                    fn params() -> crate::solution::ParamValues {
+                        #[allow(unused_mut)]
+                        let mut params = crate::solution::ParamValues::default();
+                        $($(params.set(stringify!($param), &$value.to_string());)*)?
+                        params
+                    }
+
+                    $(
+                        #[test]
+                        $(#[$attr])*
+                        fn $part() {
+                            crate::solution::check_example(&Solver, $input, &params(),
+                                                           examples!(@part $part),
+                                                           &$answer.to_string());
+                        }
+                    )+
+                }
+            )*
+        }
+    };
+    (@part part1) => { 1 };
+    (@part part2) => { 2 };
+}
+
+macro_rules! problems {
+    {$($mod_ident:ident)*} => {
+        $(
+            pub mod $mod_ident;
+        )*
+
+        /// Every problem, in order; problem `N` is at index `N - 1`
+        pub const PROBLEMS: &[&dyn solution::Problem] = &[$(&$mod_ident::Solver),*];
+    };
+}
+
+problems! {
+    p01 p02 p03 p04 p05 p06 p07
+    p08 p09 p10 p11 p12 p13 p14
+    p15 p16 p17 p18 p19 p20 p21
+    p22 p23 p24 p25
+}
@@ -1,194 +1,632 @@
 use anyhow::Result;
-use std::sync::Arc;
 
-mod grid;
-mod util;
+use aoc2022::{answers, record, solution, PROBLEMS};
+use aoc2022::solution::Problem;
 
-macro_rules! problem {
-    ($load:path => $input:ty => ()) => {
-        type Input = $input;
+mod bench;
+mod fuzz;
+mod guard;
+mod inputs;
+mod mem;
+mod repl;
+mod report;
+mod select;
+mod watch;
 
-        pub(crate) const PROBLEM: crate::Problem  = crate::Problem {
-            load_input: |d| $load(d).map(|x: $input| -> std::sync::Arc<dyn std::any::Any> {
-                std::sync::Arc::new(x)
-            }),
-            solve1: None,
-            solve2: None,
-        };
+/// Settings controlling how problems are run
+#[derive(Default)]
+struct RunOptions {
+    /// Values for problem parameters
+    params: solution::ParamValues,
+
+    /// Whether to solve parts concurrently on the rayon pool
+    parallel: bool,
+
+    /// Time limit for each stage
+    timeout: Option<std::time::Duration>,
+
+    /// Whether to count the allocations made by each stage
+    mem: bool,
+
+    /// Implementation to use for parts which have it, rather than the default
+    variant: Option<String>,
+
+    /// Whether to run every implementation of each part, failing if they disagree
+    cross_check: bool,
+}
+
+/// Load a problem's input and solve the given parts, timing each stage
+///
+/// Each stage is isolated, so that a panic (or exceeding the timeout, if one is set) is reported
+/// as a failure of that stage alone. When cross-checking, the timings reported for each part are
+/// those of its default implementation.
+fn run_problem(
+    p_num: usize,
+    prob: &'static dyn Problem,
+    dataset: &str,
+    input: Result<Box<dyn std::io::BufRead + Send>>,
+    parts: &[usize],
+    opts: &RunOptions,
+) -> report::ProblemResult {
+    use rayon::prelude::*;
+
+    let mut input = match input {
+        Ok(x) => x,
+        Err(e) => {
+            return report::ProblemResult {
+                problem: p_num,
+                dataset: dataset.to_owned(),
+                parse_time: None,
+                parse_alloc: None,
+                parts: Err(e),
+            };
+        }
     };
-    ($load:path => $input:ty => ($solve1:ident)) => {
-        type Input = $input;
-
-        pub(crate) const PROBLEM: crate::Problem = crate::Problem {
-            load_input: |d| $load(d).map(|x: Input| -> std::sync::Arc<dyn std::any::Any> {
-                std::sync::Arc::new(x)
-            }),
-            solve1: Some(|input| {
-                let input = input.downcast_ref::<Input>().expect("Inconsistent data types");
-                ($solve1)(input).map(|x: _| -> Box<dyn std::fmt::Display + Send> {Box::new(x)})
-            }),
-            solve2: None,
+
+    let params = opts.params.clone();
+    let load = move || prob.load(&mut input, &params);
+    let begin = std::time::Instant::now();
+    let (loaded, parse_alloc) = if opts.mem {
+        mem::measure(|| guard::run_guarded(opts.timeout, load))
+    } else {
+        (guard::run_guarded(opts.timeout, load), None)
+    };
+    let parse_time = Some(begin.elapsed());
+
+    let loaded: std::sync::Arc<dyn solution::Loaded> = match loaded {
+        Ok(x) => x.into(),
+        Err(e) => {
+            return report::ProblemResult {
+                problem: p_num,
+                dataset: dataset.to_owned(),
+                parse_time,
+                parse_alloc,
+                parts: Err(e.context("Failed to load input")),
+            };
+        }
+    };
+
+    let solve_variant = |part, variant: &'static str| {
+        let loaded = loaded.clone();
+        let solve = move || loaded.solve_variant(part, variant);
+        let begin = std::time::Instant::now();
+        let (answer, alloc) = if opts.mem {
+            mem::measure(|| guard::run_guarded(opts.timeout, solve))
+        } else {
+            (guard::run_guarded(opts.timeout, solve), None)
         };
+        let time = begin.elapsed();
+        report::PartResult { part, variant, answer, time, alloc }
     };
-    ($load:path => $input:ty => ($solve1:ident, $solve2:ident)) => {
-        type Input = $input;
-
-        pub(crate) const PROBLEM: crate::Problem  = crate::Problem {
-            load_input: |d| $load(d).map(|x: Input| -> std::sync::Arc<dyn std::any::Any> {
-                std::sync::Arc::new(x)
-            }),
-            solve1: Some(|input| {
-                let input = input.downcast_ref::<Input>().expect("Inconsistent data types");
-                ($solve1)(input).map(|x: _| -> Box<dyn std::fmt::Display + Send> {Box::new(x)})
-            }),
-            solve2: Some(|input| {
-                let input = input.downcast_ref::<Input>().expect("Inconsistent data types");
-                ($solve2)(input).map(|x: _| -> Box<dyn std::fmt::Display + Send> {Box::new(x)})
-            }),
+    let solve = |part| {
+        if !opts.cross_check {
+            return solve_variant(part, prob.pick_variant(part, opts.variant.as_deref()));
+        }
+
+        let mut results = prob.variants(part).into_iter().map(|v| solve_variant(part, v));
+        let mut res = results.next().unwrap();
+        let others = results.collect::<Vec<_>>();
+        let agree = |other: &report::PartResult| match (&res.answer, &other.answer) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
         };
+        if res.answer.is_ok() && !others.iter().all(agree) {
+            let answers = std::iter::once(&res).chain(others.iter())
+                         .map(|r| match &r.answer {
+                             Ok(x) => format!("{} = {}", r.variant, x.to_string().trim()),
+                             Err(e) => format!("{} failed ({})", r.variant, e),
+                         })
+                         .collect::<Vec<_>>();
+            res.answer = Err(anyhow::anyhow!("Variants disagree: {}", answers.join(", ")));
+        }
+        res
+    };
+    let parts = if opts.parallel {
+        parts.par_iter().cloned().map(solve).collect()
+    } else {
+        parts.iter().cloned().map(solve).collect()
     };
+
+    report::ProblemResult {
+        problem: p_num,
+        dataset: dataset.to_owned(),
+        parse_time,
+        parse_alloc,
+        parts: Ok(parts),
+    }
 }
 
-macro_rules! problems {
-    {$($mod_ident:ident)*} => {
-        $(
-            mod $mod_ident ;
-        )*
-        const PROBLEMS: &[Problem] = &[$($mod_ident::PROBLEM),*];
-    };
+/// Settings for recording a simulation with `--record`
+struct RecordOptions {
+    /// File to write, or directory for a sequence of images
+    path: std::path::PathBuf,
+
+    /// Most frames to keep from each part, after which the rest are dropped
+    max_frames: usize,
+
+    export: record::ExportOptions,
+}
+
+/// Load a problem's input and record each part's simulation, exporting it to a file
+///
+/// When recording more than one part, each one goes to its own file with the part number added
+/// to the name.
+fn record_problem(
+    p_num: usize,
+    prob: &'static dyn Problem,
+    inputs: &inputs::Inputs,
+    input_path: Option<&str>,
+    parts: &[usize],
+    params: &solution::ParamValues,
+    opts: &RecordOptions,
+) -> Result<()> {
+    let loaded = guard::catch(|| prob.load(&mut inputs.open(p_num, input_path)?, params))
+                 .map_err(|e| e.context("Failed to load input"))?;
+    let format = record::Format::from_path(&opts.path);
+
+    for part in parts.iter().cloned() {
+        let mut recording = record::Recording::new(opts.max_frames);
+        guard::catch(|| loaded.record(part, &mut recording))
+            .map_err(|e| e.context(format!("Failed to record part {}", part)))?;
+
+        let path = if parts.len() > 1 {
+            let stem = opts.path.file_stem().unwrap_or_default().to_string_lossy();
+            let name = match opts.path.extension() {
+                Some(ext) => format!("{}-part{}.{}", stem, part, ext.to_string_lossy()),
+                None => format!("{}-part{}", stem, part),
+            };
+            opts.path.with_file_name(name)
+        } else {
+            opts.path.clone()
+        };
+        recording.export(&path, format, &opts.export)?;
+
+        print!("Recorded {} frames of part {} to {}", recording.len(), part, path.display());
+        if recording.dropped() > 0 {
+            print!(" (dropped {} more after the limit of {})", recording.dropped(), opts.max_frames);
+        }
+        println!();
+    }
+
+    Ok(())
 }
 
-type Solver = fn(Arc<dyn std::any::Any>) -> Result<Box<dyn std::fmt::Display + Send>>;
+/// Fetch and parse the value following a command-line flag, exiting on failure
+fn flag_value<T: std::str::FromStr>(args: &mut impl Iterator<Item=String>, flag: &str) -> T {
+    let Some(value) = args.next() else {
+        eprintln!("error: {} requires a value", flag);
+        std::process::exit(1);
+    };
 
-struct Problem {
-    load_input: fn(&mut dyn std::io::BufRead) -> Result<std::sync::Arc<dyn std::any::Any>>,
-    solve1: Option<Solver>,
-    solve2: Option<Solver>,
+    match value.parse() {
+        Ok(x) => x,
+        Err(_) => {
+            eprintln!("error: invalid value for {}: {}", flag, value);
+            std::process::exit(1);
+        }
+    }
 }
 
 fn main() {
+    let mut verify = false;
+    let mut answers_path = None;
+    let mut inputs = inputs::Inputs::default();
+    let mut all_inputs = false;
+    let mut format = report::Format::Text;
+    let mut opts = RunOptions::default();
+    let mut watch = false;
+    let mut art = false;
+    let mut bench_cfg = bench::Config::default();
+    let mut fuzz_cfg = fuzz::Config::default();
+    let mut record_opts = RecordOptions {
+        path: std::path::PathBuf::new(),
+        max_frames: 2000,
+        export: record::ExportOptions::default(),
+    };
+    let mut recording = false;
+    let mut runs = None;
+    let mut skip = None;
+    let mut positional = Vec::new();
+
     let mut args = std::env::args().skip(1);
-    if let Some(prob) = args.next() {
-        // parse problem number
-        let prob_number = match prob.parse::<usize>() {
-            Ok(0) => {
-                eprintln!("error: Problem numbers are 1-based. Use #1 for the first problem.");
-                std::process::exit(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--verify" => {
+                verify = true;
             }
-            Ok(x) => x,
-            Err(_) => {
-                eprintln!("unable to parse problem number");
-                std::process::exit(1);
+            "--answers" => {
+                verify = true;
+                answers_path = Some(flag_value::<std::path::PathBuf>(&mut args, "--answers"));
             }
-        };
-        let prob_idx = prob_number - 1;
+            "--inputs" => {
+                inputs.root = flag_value(&mut args, "--inputs");
+            }
+            "--dataset" => {
+                inputs.dataset = flag_value(&mut args, "--dataset");
+            }
+            "--all-inputs" => {
+                // checking solvers against every input is the point of having several
+                verify = true;
+                all_inputs = true;
+            }
+            "--skip" => {
+                skip = Some(flag_value::<select::Selection>(&mut args, "--skip"));
+            }
+            "--parallel" => {
+                opts.parallel = true;
+            }
+            "--mem" => {
+                if !mem::ENABLED {
+                    eprintln!("error: --mem requires building with `--features alloc-stats`");
+                    std::process::exit(1);
+                }
+                opts.mem = true;
+            }
+            "--watch" => {
+                watch = true;
+            }
+            "--timeout" => {
+                let secs = flag_value::<f64>(&mut args, "--timeout");
+                match std::time::Duration::try_from_secs_f64(secs) {
+                    Ok(x) => opts.timeout = Some(x),
+                    Err(_) => {
+                        eprintln!("error: invalid value for --timeout: {}", secs);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--param" => {
+                let param = flag_value::<String>(&mut args, "--param");
+                if let Err(e) = opts.params.add(&param) {
+                    eprintln!("error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            "--variant" => {
+                opts.variant = Some(flag_value(&mut args, "--variant"));
+            }
+            "--cross-check" => {
+                opts.cross_check = true;
+            }
+            "--art" => {
+                art = true;
+            }
+            "--format" => {
+                format = flag_value(&mut args, "--format");
+            }
+            "--runs" => {
+                runs = Some(flag_value(&mut args, "--runs"));
+            }
+            "--warmup" => {
+                bench_cfg.warmup = flag_value(&mut args, "--warmup");
+            }
+            "--save-baseline" => {
+                bench_cfg.save = Some(flag_value(&mut args, "--save-baseline"));
+            }
+            "--baseline" => {
+                bench_cfg.compare = Some(flag_value(&mut args, "--baseline"));
+            }
+            "--threshold" => {
+                bench_cfg.threshold = flag_value(&mut args, "--threshold");
+            }
+            "--seed" => {
+                fuzz_cfg.seed = Some(flag_value(&mut args, "--seed"));
+            }
+            "--scale" => {
+                fuzz_cfg.scale = flag_value(&mut args, "--scale");
+            }
+            "--out" => {
+                fuzz_cfg.out = flag_value(&mut args, "--out");
+            }
+            "--record" => {
+                recording = true;
+                record_opts.path = flag_value(&mut args, "--record");
+            }
+            "--fps" => {
+                record_opts.export.fps = flag_value(&mut args, "--fps");
+            }
+            "--cell-size" => {
+                record_opts.export.cell_size = flag_value(&mut args, "--cell-size");
+            }
+            "--max-frames" => {
+                record_opts.max_frames = flag_value(&mut args, "--max-frames");
+            }
+            _ => {
+                positional.push(arg);
+            }
+        }
+    }
+
+    if opts.mem && opts.parallel {
+        eprintln!("error: --mem can't be combined with --parallel, as allocation counts are global");
+        std::process::exit(1);
+    }
+    if opts.variant.is_some() && opts.cross_check {
+        eprintln!("error: --variant can't be combined with --cross-check, which runs every variant");
+        std::process::exit(1);
+    }
+
+    if let Some(runs) = runs {
+        bench_cfg.runs = runs;
+        fuzz_cfg.runs = runs;
+    }
+    if fuzz_cfg.scale == 0 {
+        eprintln!("error: --scale must be at least 1");
+        std::process::exit(1);
+    }
+    if record_opts.export.fps <= 0.0 || record_opts.export.cell_size == 0 {
+        eprintln!("error: --fps and --cell-size must be positive");
+        std::process::exit(1);
+    }
+
+    let command = match positional.first().map(|s| s.as_str()) {
+        Some(cmd @ ("bench" | "fuzz" | "repl")) => Some(cmd.to_owned()),
+        _ => None,
+    };
+    let mut args = positional.into_iter().skip(command.is_some() as usize);
 
-        let Some(problem) = PROBLEMS.get(prob_idx) else {
-            eprintln!("invalid problem number");
+    // parse problem selection
+    let explicit = args.len() > 0;
+    let mut selection = match args.next().map(|s| s.parse::<select::Selection>()) {
+        None => select::Selection::all(),
+        Some(Ok(x)) => x,
+        Some(Err(e)) => {
+            eprintln!("error: {}", e);
             std::process::exit(1);
-        };
+        }
+    };
+    if let Some(skip) = skip.as_ref() {
+        selection.skip(skip);
+    }
+    let part_counts = PROBLEMS.iter().map(|p| p.parts()).collect::<Vec<_>>();
+    if let Err(e) = selection.validate(&part_counts) {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+    let selected = selection.problems(PROBLEMS.len());
 
-        // open input
-        let mut input: Box<dyn std::io::BufRead> = match args.next().as_deref() {
-            None => {
-                let input = std::path::Path::new("inputs").join(format!("{:02}", prob_number));
-                let input = match std::fs::File::open(input) {
-                    Ok(x) => x,
-                    Err(e) => {
-                        eprintln!("{:02}: Failed to open input: {}", prob_number, e);
-                        std::process::exit(1);
-                    }
-                };
-
-                Box::new(std::io::BufReader::new(input))
-            },
-            Some("-") => {
-                Box::new(std::io::BufReader::new(std::io::stdin()))
-            },
-            Some(name) => {
-                let input = match std::fs::File::open(name) {
-                    Ok(x) => x,
-                    Err(e) => {
-                        eprintln!("{:02}: Failed to open input: {}", prob_number, e);
-                        std::process::exit(1);
-                    }
-                };
+    // every parameter given must mean something to at least one selected problem
+    for name in opts.params.names() {
+        if !selected.iter().any(|p| PROBLEMS[p - 1].params().iter().any(|i| i.name == name)) {
+            let known = selected.iter()
+                       .flat_map(|p| PROBLEMS[p - 1].params().iter()
+                                     .map(move |i| format!("{:02}: {} = {}", p, i.name, i.default)))
+                       .collect::<Vec<_>>();
+            eprintln!("error: No selected problem has a parameter named '{}'", name);
+            if !known.is_empty() {
+                eprintln!("Available parameters:\n  {}", known.join("\n  "));
+            }
+            std::process::exit(1);
+        }
+    }
 
-                Box::new(std::io::BufReader::new(input))
-            },
-        };
+    // likewise for the variant, which needs to exist for at least one selected part
+    if let Some(name) = opts.variant.as_deref() {
+        let variants = selected.iter()
+                      .flat_map(|p| {
+                          let prob = PROBLEMS[p - 1];
+                          selection.parts(*p, prob.parts()).into_iter()
+                                   .flat_map(move |part| prob.variants(part).into_iter()
+                                                         .map(move |v| (*p, part, v)))
+                      })
+                      .filter(|(_, _, v)| *v != solution::DEFAULT_VARIANT)
+                      .collect::<Vec<_>>();
+        if !variants.iter().any(|(_, _, v)| *v == name) {
+            eprintln!("error: No selected part has a variant named '{}'", name);
+            if !variants.is_empty() {
+                let known = variants.iter()
+                           .map(|(p, part, v)| format!("{:02}p{}: {}", p, part, v))
+                           .collect::<Vec<_>>();
+                eprintln!("Available variants:\n  {}", known.join("\n  "));
+            }
+            std::process::exit(1);
+        }
+    }
+
+    let problems = selected.iter()
+                  .map(|p| (*p, PROBLEMS[p - 1], selection.parts(*p, PROBLEMS[p - 1].parts())))
+                  .collect::<Vec<_>>();
 
-        let input = match (problem.load_input)(&mut input) {
-            Ok(x) => x,
+    if command.as_deref() == Some("fuzz") {
+        guard::install_hook();
+        if let Some(timeout) = opts.timeout {
+            fuzz_cfg.timeout = timeout;
+        }
+        match fuzz::run(&fuzz_cfg, &problems, &opts.params) {
+            Ok(false) => {}
+            Ok(true) => std::process::exit(1),
             Err(e) => {
-                eprintln!("{:02}: Failed to load input: {}", prob_number, e);
+                eprintln!("error: {}", e);
                 std::process::exit(1);
             }
+        }
+        return;
+    }
+
+    if command.as_deref() == Some("repl") {
+        let [p_num] = selected[..] else {
+            eprintln!("error: The REPL can only be used with a single problem");
+            std::process::exit(1);
         };
+        let input_path = args.next();
+        if input_path.as_deref() == Some("-") {
+            eprintln!("error: The REPL reads commands from stdin, so can't read the input from it");
+            std::process::exit(1);
+        }
 
-        if let Some(p1) = problem.solve1 {
-            match (p1)(Arc::clone(&input)) {
-                Ok(x) => {
-                    println!("{:02}p1: {}", prob_number, x);
-                }
-                Err(e) => {
-                    eprintln!("{:02}: Part 1 failed: {}", prob_number, e);
-                }
+        guard::install_hook();
+        let prob = PROBLEMS[p_num - 1];
+        let res = repl::run(p_num, || {
+            let mut input = inputs.open(p_num, input_path.as_deref())?;
+            prob.load(&mut input, &opts.params)
+        });
+        if let Err(e) = res {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if recording {
+        let [p_num] = selected[..] else {
+            eprintln!("error: --record can only be used with a single problem");
+            std::process::exit(1);
+        };
+
+        guard::install_hook();
+        let prob = PROBLEMS[p_num - 1];
+        let parts = selection.parts(p_num, prob.parts());
+        if let Err(e) = record_problem(p_num, prob, &inputs, args.next().as_deref(), &parts,
+                                       &opts.params, &record_opts) {
+            eprintln!("error: {:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if command.as_deref() == Some("bench") {
+        bench_cfg.variant = opts.variant.clone();
+        bench_cfg.inputs = inputs.clone();
+        match bench::run(&bench_cfg, &problems, &opts.params) {
+            Ok(false) => {}
+            Ok(true) => {
+                eprintln!("error: Some stages regressed by more than {}%", bench_cfg.threshold);
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
             }
         }
-        if let Some(p2) = problem.solve2 {
-            match (p2)(input) {
-                Ok(x) => {
-                    println!("{:02}p2: {}", prob_number, x);
-                }
-                Err(e) => {
-                    eprintln!("{:02}: Part 2 failed: {}", prob_number, e);
-                }
+        return;
+    }
+
+    let answers = if verify {
+        match answers::Answers::load(&answers_path.unwrap_or_else(|| inputs.answers_path())) {
+            Ok(x) => Some(x),
+            Err(e) => {
+                eprintln!("error: Failed to load answers: {}", e);
+                std::process::exit(1);
             }
         }
     } else {
-        let begin = std::time::Instant::now();
-        for (idx, prob) in PROBLEMS.iter().enumerate() {
-            let p_num = idx + 1;
+        None
+    };
 
-            let input = std::path::Path::new("inputs").join(format!("{:02}", p_num));
-            let input = match std::fs::File::open(input) {
-                Ok(x) => x,
-                Err(e) => {
-                    eprintln!("{:02}: Failed to open input: {}", p_num, e);
-                    continue;
+    guard::install_hook();
+
+    let input_path = args.next();
+    if let ([prob_number], false) = (&selected[..], all_inputs) {
+        let prob_number = *prob_number;
+        let problem = PROBLEMS[prob_number - 1];
+        let parts = selection.parts(prob_number, problem.parts());
+
+        if watch {
+            let path = match input_path.as_deref() {
+                Some("-") => {
+                    eprintln!("error: Can't watch stdin for changes");
+                    std::process::exit(1);
                 }
+                Some(path) => path.into(),
+                None => inputs.path(prob_number),
             };
-            let mut input = std::io::BufReader::new(input);
 
-            let input = match (prob.load_input)(&mut input) {
-                Ok(x) => x,
+            watch::watch(&path, || {
+                let input = inputs.open(prob_number, path.to_str());
+                let res = run_problem(prob_number, problem, &inputs.dataset, input, &parts, &opts);
+
+                let mut reporter = report::Reporter::new(format, answers.as_ref(), true)
+                                  .with_times()
+                                  .with_art(art);
+                reporter.report(&res);
+                reporter.finish();
+            });
+        }
+
+        let input = inputs.open(prob_number, input_path.as_deref());
+        let res = run_problem(prob_number, problem, &inputs.dataset, input, &parts, &opts);
+
+        let mut reporter = report::Reporter::new(format, answers.as_ref(), true).with_art(art);
+        reporter.report(&res);
+        if res.parts.is_err() || !reporter.finish() {
+            std::process::exit(1);
+        }
+    } else {
+        if input_path.is_some() {
+            eprintln!("error: An input path can only be given when running a single input");
+            std::process::exit(1);
+        }
+        if watch {
+            eprintln!("error: --watch can only be used when running a single input");
+            std::process::exit(1);
+        }
+
+        // each problem's selected dataset, or all of them
+        let mut jobs = Vec::new();
+        for p_num in selected.iter().cloned() {
+            let datasets = match inputs.datasets(p_num) {
+                Ok(x) if all_inputs && !x.is_empty() => x,
+                Ok(_) => vec![inputs.dataset.clone()],
                 Err(e) => {
-                    eprintln!("{:02}: Failed to load input: {}", p_num, e);
-                    continue;
+                    eprintln!("error: Failed to list inputs for {:02}: {}", p_num, e);
+                    std::process::exit(1);
                 }
             };
+            jobs.extend(datasets.into_iter().map(|d| (p_num, inputs.with_dataset(&d))));
+        }
+        let solved = if all_inputs {
+            format!("{} inputs of {} problems", jobs.len(), selected.len())
+        } else {
+            format!("{} problems", selected.len())
+        };
 
-            if let Some(p1) = prob.solve1 {
-                if let Err(e) = (p1)(Arc::clone(&input)).map(std::hint::black_box) {
-                    eprintln!("{:02}: Part 1 failed: {}", p_num, e);
-                }
+        // only show answers when running a specific subset, not everything
+        let mut reporter = report::Reporter::new(format, answers.as_ref(), explicit)
+                          .with_art(art);
+        let run = |(p_num, inputs): &(usize, inputs::Inputs)| {
+            let prob = PROBLEMS[p_num - 1];
+            let parts = selection.parts(*p_num, prob.parts());
+            run_problem(*p_num, prob, &inputs.dataset, inputs.open(*p_num, None), &parts, &opts)
+        };
+
+        let begin = std::time::Instant::now();
+        if opts.parallel {
+            use rayon::prelude::*;
+
+            // solve everything up front, then report in problem order
+            let results = jobs.par_iter()
+                         .map(run)
+                         .collect::<Vec<_>>();
+            let dur = begin.elapsed();
+
+            let busy = results.iter()
+                      .flat_map(|res| {
+                          let parts = res.parts.as_deref().unwrap_or_default();
+                          res.parse_time.into_iter().chain(parts.iter().map(|p| p.time))
+                      })
+                      .sum::<std::time::Duration>();
+            for res in &results {
+                reporter.report(res);
             }
-            if let Some(p2) = prob.solve2 {
-                if let Err(e) = (p2)(input).map(std::hint::black_box) {
-                    eprintln!("{:02}: Part 2 failed: {}", p_num, e);
-                }
+
+            if format == report::Format::Text {
+                println!("Solved {} in {} ms ({} ms summed across solvers, {:.1}x speedup)",
+                         solved, dur.as_millis(), busy.as_millis(),
+                         busy.as_secs_f64() / dur.as_secs_f64());
+            }
+        } else {
+            for job in jobs.iter() {
+                reporter.report(&run(job));
+            }
+            let end = std::time::Instant::now();
+            let dur = end.duration_since(begin);
+            if format == report::Format::Text {
+                println!("Solved {} in {} ms", solved, dur.as_millis());
             }
         }
-        let end = std::time::Instant::now();
-        let dur = end.duration_since(begin);
-        println!("Solved {} problems in {} ms", PROBLEMS.len(), dur.as_millis());
-    }
-}
 
-problems! {
-    p01 p02 p03 p04 p05 p06 p07
-    p08 p09 p10 p11 p12 p13 p14
-    p15 p16 p17 p18 p19 p20 p21
-    p22 p23 p24 p25
+        if !reporter.finish() {
+            std::process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,129 @@
+/// Allocations made while running a single stage
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// Number of allocations, including reallocations
+    pub count: u64,
+
+    /// Total bytes requested by those allocations
+    pub bytes: u64,
+
+    /// Largest number of bytes live at once, above what was live when the stage started
+    pub peak: u64,
+}
+
+impl std::fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} allocs, {} total, {} peak",
+               self.count, fmt_bytes(self.bytes), fmt_bytes(self.peak))
+    }
+}
+
+/// Render a byte count using a sensible unit
+fn fmt_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Whether allocation counting was compiled in, with the `alloc-stats` feature
+pub const ENABLED: bool = cfg!(feature = "alloc-stats");
+
+#[cfg(feature = "alloc-stats")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    pub static COUNT: AtomicU64 = AtomicU64::new(0);
+    pub static BYTES: AtomicU64 = AtomicU64::new(0);
+    pub static LIVE: AtomicU64 = AtomicU64::new(0);
+    pub static PEAK: AtomicU64 = AtomicU64::new(0);
+
+    /// Wrapper around the system allocator which keeps allocation statistics
+    struct Counting;
+
+    impl Counting {
+        fn record_alloc(size: u64) {
+            COUNT.fetch_add(1, Ordering::Relaxed);
+            BYTES.fetch_add(size, Ordering::Relaxed);
+            let live = LIVE.fetch_add(size, Ordering::Relaxed) + size;
+            PEAK.fetch_max(live, Ordering::Relaxed);
+        }
+    }
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                Self::record_alloc(layout.size() as u64);
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                Self::record_alloc(layout.size() as u64);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            LIVE.fetch_sub(layout.size() as u64, Ordering::Relaxed);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new = System.realloc(ptr, layout, new_size);
+            if !new.is_null() {
+                LIVE.fetch_sub(layout.size() as u64, Ordering::Relaxed);
+                Self::record_alloc(new_size as u64);
+            }
+            new
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: Counting = Counting;
+}
+
+/// Count the allocations made while running a function
+///
+/// Counters are global, so this includes allocations made on other threads (such as the rayon
+/// pool) in the meantime; stages should be run one at a time for meaningful results. Returns
+/// `None` for the statistics if counting wasn't compiled in.
+pub fn measure<T, F: FnOnce() -> T>(func: F) -> (T, Option<AllocStats>) {
+    #[cfg(feature = "alloc-stats")]
+    {
+        use std::sync::atomic::Ordering;
+        use counting::*;
+
+        let count = COUNT.load(Ordering::SeqCst);
+        let bytes = BYTES.load(Ordering::SeqCst);
+        let live = LIVE.load(Ordering::SeqCst);
+        PEAK.store(live, Ordering::SeqCst);
+
+        let res = (func)();
+
+        let stats = AllocStats {
+            count: COUNT.load(Ordering::SeqCst) - count,
+            bytes: BYTES.load(Ordering::SeqCst) - bytes,
+            peak: PEAK.load(Ordering::SeqCst).saturating_sub(live),
+        };
+        (res, Some(stats))
+    }
+
+    #[cfg(not(feature = "alloc-stats"))]
+    {
+        ((func)(), None)
+    }
+}
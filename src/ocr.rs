@@ -0,0 +1,183 @@
+use anyhow::{anyhow, Result};
+
+use crate::grid::Grid;
+
+/// A block-letter font, as used by puzzles which draw their answers
+struct Font {
+    /// Height of each glyph, in cells
+    height: usize,
+
+    /// Each glyph's character and rows, with `#` for set cells
+    glyphs: &'static [(char, &'static [&'static str])],
+}
+
+/// The 4x6 font, used by most puzzles (some glyphs, like `Y`, are wider)
+const SMALL: Font = Font {
+    height: 6,
+    glyphs: &[
+        ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+        ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+        ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+        ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+        ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+        ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+        ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+        ('I', &["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+        ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+        ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+        ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+        ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+        ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+        ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+        ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('Y', &["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+        ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+    ],
+};
+
+/// The 6x10 font, used by some larger puzzles
+const LARGE: Font = Font {
+    height: 10,
+    glyphs: &[
+        ('A', &["..##..", ".#..#.", "#....#", "#....#", "#....#",
+                "######", "#....#", "#....#", "#....#", "#....#"]),
+        ('B', &["#####.", "#....#", "#....#", "#....#", "#####.",
+                "#....#", "#....#", "#....#", "#....#", "#####."]),
+        ('C', &[".####.", "#....#", "#.....", "#.....", "#.....",
+                "#.....", "#.....", "#.....", "#....#", ".####."]),
+        ('E', &["######", "#.....", "#.....", "#.....", "#####.",
+                "#.....", "#.....", "#.....", "#.....", "######"]),
+        ('F', &["######", "#.....", "#.....", "#.....", "#####.",
+                "#.....", "#.....", "#.....", "#.....", "#....."]),
+        ('G', &[".####.", "#....#", "#.....", "#.....", "#.....",
+                "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+        ('H', &["#....#", "#....#", "#....#", "#....#", "######",
+                "#....#", "#....#", "#....#", "#....#", "#....#"]),
+        ('J', &["...###", "....#.", "....#.", "....#.", "....#.",
+                "....#.", "....#.", "#...#.", "#...#.", ".###.."]),
+        ('K', &["#....#", "#...#.", "#..#..", "#.#...", "##....",
+                "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+        ('L', &["#.....", "#.....", "#.....", "#.....", "#.....",
+                "#.....", "#.....", "#.....", "#.....", "######"]),
+        ('N', &["#....#", "##...#", "##...#", "#.#..#", "#.#..#",
+                "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+        ('P', &["#####.", "#....#", "#....#", "#....#", "#####.",
+                "#.....", "#.....", "#.....", "#.....", "#....."]),
+        ('R', &["#####.", "#....#", "#....#", "#....#", "#####.",
+                "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+        ('X', &["#....#", "#....#", ".#..#.", ".#..#.", "..##..",
+                "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+        ('Z', &["######", ".....#", ".....#", "....#.", "...#..",
+                "..#...", ".#....", "#.....", "#.....", "######"]),
+    ],
+};
+
+impl Font {
+    /// Find the character whose glyph matches a block of cells exactly
+    fn lookup(&self, cell: impl Fn(usize, usize) -> bool, width: usize) -> Option<char> {
+        self.glyphs.iter()
+            .find(|(_, rows)| {
+                rows[0].len() == width &&
+                rows.iter().enumerate().all(|(y, row)| {
+                    row.bytes().enumerate().all(|(x, c)| (c == b'#') == (cell)(x, y))
+                })
+            })
+            .map(|(c, _)| *c)
+    }
+}
+
+/// Decode block letters drawn on a grid into text
+///
+/// The font is chosen by the height of the drawing, once any empty rows above and below it are
+/// removed. Letters must be separated by at least one empty column. Fails if the drawing isn't
+/// the height of a known font, or if any letter isn't recognized.
+pub fn decode(grid: &Grid<bool>) -> Result<String> {
+    let row_set = |y: usize| grid.row_iter(y).any(|b| *b);
+    let top = (0..grid.height()).find(|y| row_set(*y))
+             .ok_or_else(|| anyhow!("Grid is empty"))?;
+    let bottom = (0..grid.height()).rev().find(|y| row_set(*y)).unwrap();
+    let height = bottom - top + 1;
+
+    let font = [SMALL, LARGE].into_iter()
+              .find(|f| f.height == height)
+              .ok_or_else(|| anyhow!("No font is {} cells high", height))?;
+
+    let col_set = |x: usize| (top..=bottom).any(|y| *grid.get((x, y)));
+    let mut out = String::new();
+    let mut x = 0;
+    while x < grid.width() {
+        if !col_set(x) {
+            x += 1;
+            continue;
+        }
+
+        let start = x;
+        while x < grid.width() && col_set(x) {
+            x += 1;
+        }
+
+        let c = font.lookup(|gx, gy| *grid.get((start + gx, top + gy)), x - start)
+                    .ok_or_else(|| anyhow!("Unrecognized letter at column {}", start))?;
+        out.push(c);
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(art: &[&str]) -> Grid<bool> {
+        let data = art.iter().flat_map(|row| row.bytes().map(|c| c == b'#')).collect();
+        Grid::from_data(data, art[0].len())
+    }
+
+    #[test]
+    fn small_font() {
+        let grid = parse(&[
+            "###  #  # #   # ####",
+            "#  # #  # #   #    #",
+            "#  # ####  # #    # ",
+            "###  #  #   #    #  ",
+            "#    #  #   #   #   ",
+            "#    #  #   #   ####",
+        ]);
+        assert_eq!(decode(&grid).unwrap(), "PHYZ");
+    }
+
+    #[test]
+    fn large_font() {
+        let grid = parse(&[
+            "#    #  ####  ",
+            "#    # #    # ",
+            "#    # #      ",
+            "#    # #      ",
+            "###### #      ",
+            "#    # #  ### ",
+            "#    # #    # ",
+            "#    # #    # ",
+            "#    # #   ## ",
+            "#    #  ### # ",
+        ]);
+        assert_eq!(decode(&grid).unwrap(), "HG");
+    }
+
+    #[test]
+    fn not_text() {
+        assert!(decode(&Grid::filled(5, 6, false)).is_err());
+        assert!(decode(&Grid::filled(5, 7, true)).is_err());
+
+        // a W isn't part of the font
+        let grid = parse(&[
+            "#   #",
+            "#   #",
+            "# # #",
+            "# # #",
+            "# # #",
+            " # # ",
+        ]);
+        assert!(decode(&grid).is_err());
+    }
+}
@@ -1,20 +1,19 @@
 use anyhow::{anyhow, Result};
 
-use std::str::FromStr;
+use crate::gen::Rng;
+use crate::parse::{self, Cursor};
 
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
     let mut out = Vec::new();
     let mut current = Vec::new();
 
-    let mut line = String::new();
-    while input.read_line(&mut line)? > 0 {
-        if line.trim().is_empty() {
+    let data = parse::read_all(input)?;
+    for line in Cursor::new(&data).lines().map(Cursor::trim) {
+        if line.is_empty() {
             out.push(std::mem::take(&mut current));
         } else {
-            let n = u64::from_str(line.trim())?;
-            current.push(n);
+            current.push(line.parse::<u64>()?);
         }
-        line.clear();
     }
 
     if !current.is_empty() {
@@ -40,4 +39,37 @@ fn solve2(elves: &Input) -> Result<u64> {
     Ok(data[data.len()-3..].iter().sum::<u64>())
 }
 
-problem!(load_input => Vec<Vec<u64>> => (solve1, solve2));
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    let elves = (0..rng.count(3..=3 + 2 * scale))
+               .map(|_| {
+                   (0..rng.count(1..=5))
+                       .map(|_| format!("{}\n", rng.range(1000..=60000)))
+                       .collect::<String>()
+               })
+               .collect::<Vec<_>>();
+    elves.join("\n")
+}
+
+problem!(load_input => Vec<Vec<u64>> => (solve1 -> u64, solve2 -> u64), generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000
+";
+
+examples! {
+    example: EXAMPLE => part1 = 24000, part2 = 45000;
+}
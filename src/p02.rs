@@ -1,5 +1,7 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 
+use crate::gen::Rng;
+use crate::parse::Cursor;
 use crate::util;
 
 #[derive(Copy, Clone, PartialEq)]
@@ -54,12 +56,25 @@ impl Play {
 const MOVES: &[Play] = &[Play::Rock, Play::Paper, Play::Scissors];
 
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
-    util::read_lines(input, |line| {
-        let a = line.chars().next().ok_or_else(|| anyhow!("No first value on line"))?;
-        let b = line.chars().nth(2).ok_or_else(|| anyhow!("No first value on line"))?;
+    /// Read a letter from the three starting at `first`, as an index
+    fn letter(line: &mut Cursor, first: u8) -> Result<u8> {
+        match line.peek() {
+            Some(c) if (first..first + 3).contains(&(c as u8)) => {
+                line.next_char();
+                Ok(c as u8 - first)
+            }
+            _ => Err(line.error(format!("Expected one of {}", (first..first + 3).map(|c| c as char)
+                                                                  .collect::<String>()))),
+        }
+    }
 
-        Ok((a as u8 - b'A',
-            b as u8 - b'X'))
+    util::read_lines(input, |mut line| {
+        let a = letter(&mut line, b'A')?;
+        line.expect(" ")?;
+        let b = letter(&mut line, b'X')?;
+        line.finish()?;
+
+        Ok((a, b))
     })
 }
 
@@ -80,4 +95,22 @@ fn solve2(input: &Input) -> Result<usize> {
             .sum())
 }
 
-problem!(load_input => Vec<(u8, u8)> => (solve1, solve2));
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    (0..rng.count(1..=10 * scale))
+        .map(|_| format!("{} {}\n", (b'A' + rng.below(3) as u8) as char,
+                                    (b'X' + rng.below(3) as u8) as char))
+        .collect()
+}
+
+problem!(load_input => Vec<(u8, u8)> => (solve1 -> usize, solve2 -> usize), generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+A Y
+B X
+C Z
+";
+
+examples! {
+    example: EXAMPLE => part1 = 15, part2 = 12;
+}
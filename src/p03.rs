@@ -1,5 +1,9 @@
 use anyhow::{anyhow, Result};
 
+use std::collections::HashSet;
+
+use crate::gen::Rng;
+
 #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
 struct Item(u8);
 
@@ -9,6 +13,15 @@ impl Item {
     }
 }
 
+impl From<Item> for char {
+    fn from(item: Item) -> char {
+        match item.0 {
+            0..=25 => (b'a' + item.0) as char,
+            _ => (b'A' + item.0 - 26) as char,
+        }
+    }
+}
+
 impl TryFrom<char> for Item {
     type Error = anyhow::Error;
 
@@ -53,7 +66,7 @@ impl ItemSet {
     }
 }
 
-struct Rucksack {
+pub struct Rucksack {
     items: Vec<Item>,
 }
 
@@ -71,11 +84,37 @@ impl Rucksack {
                 .intersect(&ItemSet::from_items(right))
                 .only_item()
     }
+
+    /// Find the misplaced item by intersecting hash sets
+    fn misplaced_item_naive(&self) -> Option<Item> {
+        let (left, right) = self.compartments();
+        let left = left.iter().collect::<HashSet<_>>();
+        let right = right.iter().collect::<HashSet<_>>();
+        only(left.intersection(&right).map(|i| **i))
+    }
+}
+
+/// The item in a collection, if there's exactly one distinct item
+fn only(items: impl Iterator<Item=Item>) -> Option<Item> {
+    let items = items.collect::<HashSet<_>>();
+    if items.len() == 1 {
+        items.into_iter().next()
+    } else {
+        None
+    }
 }
 
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
     crate::util::read_lines(input, |line| {
-        let items = line.chars().map(Item::try_from).collect::<Result<_>>()?;
+        let mut items = Vec::new();
+        let mut rest = line;
+        while let Some(c) = rest.peek() {
+            items.push(Item::try_from(c).map_err(|e| rest.error(e))?);
+            rest.next_char();
+        }
+        if items.len() % 2 != 0 {
+            return Err(line.error_at(line.rest(), "Rucksack has an odd number of items"));
+        }
         Ok(Rucksack { items })
     })
 }
@@ -102,4 +141,85 @@ fn solve2(input: &Input) -> Result<usize> {
     Ok(out)
 }
 
-problem!(load_input => Vec<Rucksack> => (solve1, solve2));
+fn solve1_naive(input: &Input) -> Result<usize> {
+    input.iter()
+         .map(|sack| {
+             sack.misplaced_item_naive()
+                 .map(|i| i.priority())
+                 .ok_or_else(|| anyhow!("No misplaced item found"))
+         })
+         .sum()
+}
+
+fn solve2_naive(input: &Input) -> Result<usize> {
+    let mut out = 0;
+    for group in input.chunks(3) {
+        anyhow::ensure!(group.len() == 3);
+        let first = &group[0].items;
+        let badge = only(first.iter()
+                              .filter(|i| group[1..].iter().all(|sack| sack.items.contains(i)))
+                              .cloned())
+                   .ok_or_else(|| anyhow!("No single badge item found"))?;
+
+        out += badge.priority();
+    }
+
+    Ok(out)
+}
+
+/// Generate groups of three sacks, each with exactly one misplaced item and one shared badge
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..rng.count(1..=scale) {
+        let mut items = (0..52).map(Item).collect::<Vec<_>>();
+        rng.shuffle(&mut items);
+        let badge = items.pop().unwrap();
+
+        // each sack draws from two of three pools, so nothing else is in all three sacks
+        let pools = items.chunks(items.len() / 3).collect::<Vec<_>>();
+        for sack in 0..3 {
+            let mut kinds = [pools[sack], pools[(sack + 1) % 3]].concat();
+            kinds.push(badge);
+            rng.shuffle(&mut kinds);
+
+            let misplaced = kinds.pop().unwrap();
+            let badge_left = rng.chance(0.5);
+            let (left_kinds, right_kinds) = kinds.split_at(kinds.len() / 2);
+            let half = rng.count(2..=12);
+
+            let mut halves = [vec![misplaced], vec![misplaced]];
+            if misplaced != badge {
+                halves[if badge_left { 0 } else { 1 }].push(badge);
+            }
+            for (half_items, kinds) in halves.iter_mut().zip([left_kinds, right_kinds]) {
+                let kinds = kinds.iter().filter(|k| **k != badge).cloned().collect::<Vec<_>>();
+                while half_items.len() < half {
+                    half_items.push(*rng.pick(&kinds));
+                }
+                rng.shuffle(half_items);
+            }
+
+            out.extend(halves.iter().flatten().map(|i| char::from(*i)));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+problem!(load_input => Vec<Rucksack> => (solve1 -> usize, solve2 -> usize),
+         variants { part1: naive = solve1_naive, part2: naive = solve2_naive },
+         generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw
+";
+
+examples! {
+    example: EXAMPLE => part1 = 157, part2 = 70;
+}
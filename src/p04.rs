@@ -1,9 +1,10 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 
-use std::str::FromStr;
+use crate::gen::Rng;
+use crate::parse::Cursor;
 
 #[derive(Copy, Clone, Debug)]
-struct Range {
+pub struct Range {
     start: u8,
     end: u8,
 }
@@ -20,19 +21,20 @@ impl Range {
 }
 
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
-    fn parse_range(s: &str) -> Result<Range> {
-        let (a, b) = s.split_once('-').ok_or_else(|| anyhow!("No separating dash on input"))?;
-        let start = u8::from_str(a)?;
-        let end = u8::from_str(b)?;
+    fn parse_range(line: &mut Cursor) -> Result<Range> {
+        let start = line.number()?;
+        line.expect("-")?;
+        let end = line.number()?;
 
         Ok(Range {start, end})
     }
 
-    crate::util::read_lines(input, |line| {
-        let (a, b) = line.split_once(',')
-                    .ok_or_else(|| anyhow!("No splitting comma on input line"))?;
-        Ok((parse_range(a)?,
-            parse_range(b)?))
+    crate::util::read_lines(input, |mut line| {
+        let a = parse_range(&mut line)?;
+        line.expect(",")?;
+        let b = parse_range(&mut line)?;
+        line.finish()?;
+        Ok((a, b))
     })
 }
 
@@ -44,4 +46,35 @@ fn solve2(input: &Input) -> Result<usize> {
     Ok(input.iter().filter(|(a, b)| a.overlaps(b)).count())
 }
 
-problem!(load_input => Vec<(Range, Range)> => (solve1, solve2));
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    let count = rng.count(1..=10 * scale);
+    let mut range = |lo: i64, hi: i64| {
+        let start = rng.range(lo..=hi);
+        format!("{}-{}", start, rng.range(start..=hi))
+    };
+
+    // make sure at least one pair has one range containing the other
+    let mut pairs = vec![format!("1-99,{}\n", range(1, 99))];
+    for _ in 0..count {
+        pairs.push(format!("{},{}\n", range(1, 99), range(1, 99)));
+    }
+    rng.shuffle(&mut pairs);
+    pairs.concat()
+}
+
+problem!(load_input => Vec<(Range, Range)> => (solve1 -> usize, solve2 -> usize),
+         generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8
+";
+
+examples! {
+    example: EXAMPLE => part1 = 2, part2 = 4;
+}
@@ -1,6 +1,8 @@
 use anyhow::{anyhow, Result};
 
-use std::str::FromStr;
+use crate::gen::Rng;
+use crate::parse::{self, Cursor};
+use crate::solution::Inspect;
 
 type Crate = char;
 
@@ -42,24 +44,25 @@ impl Stacks {
     }
 }
 
-struct Problem {
+pub struct Problem {
     stacks: Stacks,
     moves: Vec<Move>,
 }
 
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
+    let data = parse::read_all(input)?;
+    let mut cur = Cursor::new(&data);
+
     // parse initial state
     let stacks = {
-        let mut content = String::new();
-        while input.read_line(&mut content)? > 1 {
-        }
+        let mut lines = cur.next_block()
+                       .ok_or_else(|| anyhow!("No starting state"))?
+                       .lines()
+                       .collect::<Vec<_>>();
 
         // look at the last line - how many columns are there?
-        content.truncate(content.trim_end().len());
-        let mut lines = content.lines().rev();
-
-        let last_line = lines.next().ok_or_else(|| anyhow!("No trailing state line"))?;
-        let column_positions = last_line.chars()
+        let last_line = lines.pop().ok_or_else(|| anyhow!("No trailing state line"))?;
+        let column_positions = last_line.rest().chars()
                               .enumerate()
                               .filter(|(_, c)| !c.is_whitespace())
                               .map(|(idx, _)| idx)
@@ -67,26 +70,42 @@ fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
 
         // transpose into columns
         let mut stacks = vec![Vec::new(); column_positions.len()];
-        for line in lines {
-            let chars = line.chars().collect::<Vec<_>>();
+        for line in lines.iter().rev() {
+            let chars = line.rest().chars().collect::<Vec<_>>();
             for (line_char, stack) in column_positions.iter()
-                                     .map(|pos| chars[*pos])
+                                     .filter_map(|pos| chars.get(*pos))
                                      .zip(stacks.iter_mut())
                                      .filter(|(ch, _)| !ch.is_whitespace()) {
-                stack.push(line_char);
+                stack.push(*line_char);
             }
         }
 
         Stacks { data: stacks }
     };
 
-    let moves = crate::util::read_lines_regex(input, r#"^move (\d+) from (\d+) to (\d+)$"#, |cap| {
-        Ok(Move {
-            count: usize::from_str(&cap[1])?,
-            from: usize::from_str(&cap[2])? - 1, // offset indices
-            to: usize::from_str(&cap[3])? - 1,
-        })
-    })?;
+    let stack = |line: &mut Cursor| -> Result<usize> {
+        let mark = *line;
+        let n = line.number::<usize>()?;
+        if n == 0 || n > stacks.data.len() {
+            return Err(line.error_at(line.since(mark), format!("No stack {}", n)));
+        }
+        Ok(n - 1) // offset indices
+    };
+
+    let moves = cur.lines()
+                .map(Cursor::trim)
+                .filter(|line| !line.is_empty())
+                .map(|mut line| {
+                    line.expect("move ")?;
+                    let count = line.number()?;
+                    line.expect(" from ")?;
+                    let from = stack(&mut line)?;
+                    line.expect(" to ")?;
+                    let to = stack(&mut line)?;
+                    line.finish()?;
+                    Ok(Move { count, from, to })
+                })
+                .collect::<Result<Vec<_>>>()?;
 
     Ok(Problem { stacks, moves })
 }
@@ -111,4 +130,109 @@ fn solve2(input: &Input) -> Result<String> {
     Ok(stacks.data.into_iter().flat_map(|stack| stack.last().cloned()).collect())
 }
 
-problem!(load_input => Problem => (solve1, solve2));
+/// Stepping through the moves one at a time
+struct Inspector<'a> {
+    input: &'a Problem,
+    stacks: Stacks,
+    done: usize,
+    part2: bool,
+}
+
+impl Inspect for Inspector<'_> {
+    fn step(&mut self) -> Result<bool> {
+        let Some(mv) = self.input.moves.get(self.done) else {
+            return Ok(false);
+        };
+
+        if self.part2 {
+            self.stacks.apply_move_part2(*mv)?;
+        } else {
+            self.stacks.apply_move(*mv)?;
+        }
+        self.done += 1;
+        Ok(true)
+    }
+
+    fn show(&self) -> String {
+        // draw the stacks the same way as the input
+        let height = self.stacks.data.iter().map(|s| s.len()).max().unwrap_or(0);
+        let mut out = String::new();
+        for level in (0..height).rev() {
+            let row = self.stacks.data.iter()
+                     .map(|s| s.get(level).map_or("   ".to_owned(), |c| format!("[{}]", c)))
+                     .collect::<Vec<_>>();
+            out += &format!("{}\n", row.join(" ").trim_end());
+        }
+        let labels = (1..=self.stacks.data.len()).map(|i| format!(" {} ", i)).collect::<Vec<_>>();
+        out += &format!("{}\n\n", labels.join(" "));
+
+        out += &match self.input.moves.get(self.done) {
+            Some(mv) => format!("Move {} of {}: move {} from {} to {}",
+                                self.done + 1, self.input.moves.len(), mv.count, mv.from + 1, mv.to + 1),
+            None => format!("All {} moves done", self.input.moves.len()),
+        };
+        out
+    }
+}
+
+fn inspect(input: &Input, part: usize) -> Inspector<'_> {
+    Inspector { input, stacks: input.stacks.clone(), done: 0, part2: part == 2 }
+}
+
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    let mut heights = (0..rng.count(3..=9)).map(|_| rng.count(0..=scale + 2)).collect::<Vec<_>>();
+    if heights.iter().all(|h| *h == 0) {
+        heights[0] = 1;
+    }
+
+    let top = *heights.iter().max().unwrap();
+    let mut out = String::new();
+    for level in (0..top).rev() {
+        let row = heights.iter()
+                         .map(|h| if *h > level {
+                             format!("[{}]", (b'A' + rng.below(26) as u8) as char)
+                         } else {
+                             "   ".to_owned()
+                         })
+                         .collect::<Vec<_>>();
+        out += &format!("{}\n", row.join(" "));
+    }
+    let labels = (1..=heights.len()).map(|i| format!(" {} ", i)).collect::<Vec<_>>();
+    out += &format!("{}\n\n", labels.join(" "));
+
+    // only the heights matter for keeping moves valid
+    for _ in 0..rng.count(1..=5 * scale) {
+        let from = loop {
+            let i = rng.below(heights.len());
+            if heights[i] > 0 {
+                break i;
+            }
+        };
+        let to = (from + rng.count(1..=heights.len() - 1)) % heights.len();
+        let count = rng.count(1..=heights[from]);
+        heights[from] -= count;
+        heights[to] += count;
+        out += &format!("move {} from {} to {}\n", count, from + 1, to + 1);
+    }
+    out
+}
+
+problem!(load_input => Problem => (solve1 -> String, solve2 -> String),
+         generator generate, inspect inspect);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2
+";
+
+examples! {
+    example: EXAMPLE => part1 = "CMZ", part2 = "MCD";
+}
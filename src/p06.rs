@@ -1,5 +1,7 @@
 use anyhow::{anyhow, Result};
 
+use crate::gen::Rng;
+
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
     let mut out = String::new();
     anyhow::ensure!(input.read_line(&mut out)? > 0, "No input data");
@@ -28,4 +30,27 @@ fn solve2(s: &Input) -> Result<usize> {
      find_marker(s, 14).ok_or_else(|| anyhow!("No matching position"))
 }
 
-problem!(load_input => Vec<char> => (solve1, solve2));
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    // a few letters with plenty of repeats, then a guaranteed marker somewhere later
+    let mut out = (0..rng.count(0..=20 * scale))
+                 .map(|_| (b'a' + rng.below(3) as u8) as char)
+                 .collect::<String>();
+    let mut letters = ('a'..='z').collect::<Vec<_>>();
+    rng.shuffle(&mut letters);
+    out.extend(&letters[..14]);
+    let rest = rng.count(0..=20 * scale);
+    out += &rng.word(rest);
+    out.push('\n');
+    out
+}
+
+problem!(load_input => Vec<char> => (solve1 -> usize, solve2 -> usize),
+         positive { part1, part2 }, generator generate);
+
+examples! {
+    example1: "mjqjpqmgbljsphdztnvjfqwrcgsmlb" => part1 = 7, part2 = 19;
+    example2: "bvwbjplbgvbhsrlpgdmjqwftvncz" => part1 = 5, part2 = 23;
+    example3: "nppdvjthqldpwncqszvftbrmjlhg" => part1 = 6, part2 = 23;
+    example4: "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg" => part1 = 10, part2 = 29;
+    example5: "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw" => part1 = 11, part2 = 26;
+}
@@ -1,10 +1,12 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 
-use std::collections::HashMap;
-use std::str::FromStr;
+use std::collections::{HashMap, HashSet};
+use crate::gen::Rng;
+use crate::parse::{self, Cursor};
+use crate::solution::Inspect;
 
 #[derive(Debug)]
-enum Node {
+pub enum Node {
     /// File with the given size
     File(u64),
 
@@ -40,41 +42,40 @@ impl Node {
 }
 
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
-    let lines = crate::util::read_lines(input, |line| Ok(line.to_owned()))?;
+    let data = parse::read_all(input)?;
 
-    fn build_tree<'a, I: Iterator<Item=&'a str>>(
+    fn build_tree<'a, I: Iterator<Item=Cursor<'a>>>(
         lines: &mut I,
         root: &mut HashMap<String, Node>
     ) -> Result<()> {
-        while let Some(line) = lines.next() {
-            if line.starts_with("$ cd ") {
+        while let Some(mut line) = lines.next() {
+            if line.eat("$ cd ") {
                 // move up or down
-                let name = line.trim_start_matches("$ cd ");
+                let name = line.rest();
                 if name == ".." {
                     break;
                 } else {
                     let child = root.get_mut(name)
-                               .ok_or_else(|| anyhow!("Invalid hierarchy ref: {}", name))?;
+                               .ok_or_else(|| line.error_at(name, "No such directory"))?;
                     let Node::Dir {children, ..} = child else {
-                        anyhow::bail!("Invalid hierarchy cast");
+                        return Err(line.error_at(name, "Not a directory"));
                     };
 
                     build_tree(lines, children)?;
                 }
-            } else if line.starts_with("$ ls") {
+            } else if line.eat("$ ls") {
                 // begin listing (ignore this case)
-            } else if line.starts_with("dir ") {
+            } else if line.eat("dir ") {
                 // dir entry
-                let (_, name) = line.split_once(' ').ok_or_else(|| anyhow!("Invalid file line"))?;
-                root.insert(name.to_owned(), Node::Dir {
+                root.insert(line.rest().to_owned(), Node::Dir {
                     total_size: 0,
                     children: HashMap::new()
                 });
             } else {
                 // file entry
-                let (num, name) = line.split_once(' ').ok_or_else(|| anyhow!("Invalid file line"))?;
-                let size = u64::from_str(num)?;
-                root.insert(name.to_owned(), Node::File(size));
+                let size = line.number::<u64>()?;
+                line.expect(" ")?;
+                root.insert(line.rest().to_owned(), Node::File(size));
             }
         }
 
@@ -82,8 +83,12 @@ fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
     }
 
     let mut children = HashMap::new();
-    let mut lines_iter = lines.iter().map(|s| s.as_str()).skip(1);
-    build_tree(&mut lines_iter, &mut children)?;
+    let mut lines = Cursor::new(&data).lines().map(Cursor::trim).filter(|l| !l.is_empty());
+    if let Some(mut first) = lines.next() {
+        first.expect("$ cd /")?;
+        first.finish()?;
+    }
+    build_tree(&mut lines, &mut children)?;
 
     let mut out = Node::Dir {total_size: 0, children};
     out.update_sizes();
@@ -91,10 +96,13 @@ fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
     Ok(out)
 }
 
-fn solve1(input: &Input) -> Result<u64> {
+/// Largest directory counted by part 1
+const SMALL_DIR: u64 = 100_000;
+
+fn solve1(input: &Input, _params: &Params) -> Result<u64> {
     let mut out = 0;
     input.on_dirs(&mut |size, _| {
-        if size <= 100_000 {
+        if size <= SMALL_DIR {
             out += size;
         }
     });
@@ -102,13 +110,11 @@ fn solve1(input: &Input) -> Result<u64> {
     Ok(out)
 }
 
-fn solve2(input: &Input) -> Result<u64> {
-    const TOTAL: u64 = 70_000_000;
-    const NEEDED: u64 = 30_000_000;
-
+fn solve2(input: &Input, params: &Params) -> Result<u64> {
     // how much do we need to free up?
     let Node::Dir {total_size: used, ..} = input else { unreachable!() };
-    let to_free = NEEDED - (TOTAL - used);
+    anyhow::ensure!(*used <= params.total, "Filesystem is larger than the disk");
+    let to_free = params.needed.saturating_sub(params.total - used);
 
     // find smallest dir to delete which fits the criteria
     let mut to_del: Option<u64> = None;
@@ -127,4 +133,176 @@ fn solve2(input: &Input) -> Result<u64> {
     Ok(to_del.unwrap())
 }
 
-problem!(load_input => Node => (solve1, solve2));
+/// Listing of the directory tree, marking the directories a part is looking for
+///
+/// There's nothing to step through here, as the tree is built while loading the input.
+struct Inspector<'a> {
+    root: &'a Node,
+
+    /// Directories of at least this size are marked, or of at most it if `smaller` is set
+    limit: u64,
+    smaller: bool,
+}
+
+impl Inspect for Inspector<'_> {
+    fn step(&mut self) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn show(&self) -> String {
+        fn draw(name: &str, node: &Node, depth: usize, insp: &Inspector, out: &mut String) {
+            let indent = "  ".repeat(depth);
+            match node {
+                Node::File(size) => {
+                    *out += &format!("{}- {} (file, size={})\n", indent, name, size);
+                }
+                Node::Dir {total_size, children} => {
+                    let marked = if insp.smaller { *total_size <= insp.limit }
+                                 else { *total_size >= insp.limit };
+                    *out += &format!("{}- {} (dir, size={}){}\n",
+                                     indent, name, total_size, if marked { " *" } else { "" });
+
+                    let mut names = children.keys().collect::<Vec<_>>();
+                    names.sort();
+                    for name in names {
+                        draw(name, &children[name], depth + 1, insp, out);
+                    }
+                }
+            }
+        }
+
+        let mut out = String::new();
+        draw("/", self.root, 0, self, &mut out);
+        out += &if self.smaller {
+            format!("\n* at most {}", self.limit)
+        } else {
+            format!("\n* at least {}, enough to free for the update", self.limit)
+        };
+        out
+    }
+}
+
+fn inspect<'a>(input: &'a Input, params: &Params, part: usize) -> Inspector<'a> {
+    let Node::Dir {total_size: used, ..} = input else { unreachable!() };
+    match part {
+        1 => Inspector { root: input, limit: SMALL_DIR, smaller: true },
+        _ => Inspector {
+            root: input,
+            limit: params.needed.saturating_sub(params.total.saturating_sub(*used)),
+            smaller: false,
+        },
+    }
+}
+
+/// Generate a terminal session exploring a random tree, which is too full for the update
+fn generate(rng: &mut Rng, scale: usize, params: &Params) -> String {
+    struct Dir {
+        names: HashSet<String>,
+        files: Vec<(String, u64)>,
+        dirs: Vec<(String, usize)>,
+    }
+
+    fn name(rng: &mut Rng, dir: &mut Dir) -> String {
+        loop {
+            let len = rng.count(1..=6);
+            let name = rng.word(len);
+            if dir.names.insert(name.clone()) {
+                break name;
+            }
+        }
+    }
+
+    let mut dirs = vec![Dir { names: HashSet::new(), files: Vec::new(), dirs: Vec::new() }];
+    for idx in 1..=rng.count(1..=2 * scale) {
+        let parent = rng.below(idx);
+        let name = name(rng, &mut dirs[parent]);
+        dirs[parent].dirs.push((name, idx));
+        dirs.push(Dir { names: HashSet::new(), files: Vec::new(), dirs: Vec::new() });
+    }
+
+    // every dir gets a file, and the last one is a leaf which is kept small for part 1
+    let files = (0..dirs.len()).chain((0..rng.count(0..=3 * scale)).map(|_| rng.below(dirs.len())))
+                               .collect::<Vec<_>>();
+    let tiny = dirs.len() - 1;
+    let cap = (params.total / (4 * files.len() as u64)).clamp(1, 300_000);
+    let mut used = 0;
+    for dir in files {
+        let size = rng.range(1..=if dir == tiny { 1000 } else { cap as i64 }) as u64;
+        let name = name(rng, &mut dirs[dir]);
+        dirs[dir].files.push((name, size));
+        used += size;
+    }
+
+    // then fill the disk enough that something has to be deleted
+    let low = params.total.saturating_sub(params.needed) + 1;
+    if low <= params.total {
+        let target = rng.range(low as i64..=params.total as i64) as u64;
+        if target > used {
+            let name = name(rng, &mut dirs[0]);
+            dirs[0].files.push((name, target - used));
+        }
+    }
+
+    fn list(rng: &mut Rng, dirs: &mut [Dir], idx: usize, out: &mut String) {
+        let mut entries = dirs[idx].files.iter()
+                                         .map(|(name, size)| format!("{} {}", size, name))
+                                         .chain(dirs[idx].dirs.iter().map(|(name, _)| format!("dir {}", name)))
+                                         .collect::<Vec<_>>();
+        rng.shuffle(&mut entries);
+        *out += "$ ls\n";
+        for entry in entries {
+            *out += &format!("{}\n", entry);
+        }
+
+        let mut children = dirs[idx].dirs.clone();
+        rng.shuffle(&mut children);
+        for (name, child) in children {
+            *out += &format!("$ cd {}\n", name);
+            list(rng, dirs, child, out);
+            *out += "$ cd ..\n";
+        }
+    }
+
+    let mut out = "$ cd /\n".to_owned();
+    list(rng, &mut dirs, 0, &mut out);
+    out
+}
+
+problem!(load_input => Node => (solve1 -> u64, solve2 -> u64), params {
+    /// Size of the disk
+    total: u64 = 70_000_000,
+
+    /// Free space needed for the update
+    needed: u64 = 30_000_000,
+}, generator generate, inspect inspect);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k
+";
+
+examples! {
+    example: EXAMPLE => part1 = 95437, part2 = 24933642;
+}
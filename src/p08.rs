@@ -1,10 +1,11 @@
 use anyhow::Result;
 
+use crate::gen::Rng;
 use crate::grid::{Grid, GridPoint};
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 #[repr(transparent)]
-struct Tree(u8);
+pub struct Tree(u8);
 
 impl TryFrom<char> for Tree {
     type Error = anyhow::Error;
@@ -79,4 +80,29 @@ fn solve2(input: &Input) -> Result<usize> {
     input.points().map(score).max().ok_or_else(|| anyhow::anyhow!("No points on input grid"))
 }
 
-problem!(crate::util::load_grid => Grid<Tree> => (solve1, solve2));
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    let width = rng.count(5..=5 + 5 * scale);
+    (0..rng.count(5..=5 + 5 * scale))
+        .map(|_| {
+            let mut row = (0..width).map(|_| (b'0' + rng.below(10) as u8) as char).collect::<String>();
+            row.push('\n');
+            row
+        })
+        .collect()
+}
+
+problem!(crate::util::load_grid => Grid<Tree> => (solve1 -> usize, solve2 -> usize),
+         generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+30373
+25512
+65332
+33549
+35390
+";
+
+examples! {
+    example: EXAMPLE => part1 = 21, part2 = 8;
+}
@@ -1,4 +1,8 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
+
+use crate::gen::Rng;
+use crate::grid::{Grid, InfiniteGrid};
+use crate::record::{record_with, Recorder};
 
 #[derive(Copy, Clone)]
 enum Direction {
@@ -9,23 +13,23 @@ enum Direction {
 }
 
 #[derive(Copy, Clone)]
-struct Motion {
+pub struct Motion {
     dir: Direction,
     count: usize,
 }
 
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
-    crate::util::read_lines(input, |line| {
-        let parts = line.split_once(' ')
-                        .ok_or_else(|| anyhow!("Missing separator on input line"))?;
-        let dir = match parts.0 {
-            "L" => Direction::Left,
-            "R" => Direction::Right,
-            "U" => Direction::Up,
-            "D" => Direction::Down,
-            _ => anyhow::bail!("Invalid direction character"),
+    crate::util::read_lines(input, |mut line| {
+        let dir = match line.peek() {
+            Some('L') => Direction::Left,
+            Some('R') => Direction::Right,
+            Some('U') => Direction::Up,
+            Some('D') => Direction::Down,
+            _ => return Err(line.error("Invalid direction character")),
         };
-        let count = parts.1.parse::<usize>()?;
+        line.next_char();
+        line.expect(" ")?;
+        let count = line.parse::<usize>()?;
 
         Ok(Motion {dir, count})
     })
@@ -99,32 +103,92 @@ impl Rope {
     }
 }
 
-fn solve1(input: &Input) -> Result<usize> {
-    let mut rope = Rope::new((0, 0), 2);
-    let mut positions = std::collections::HashSet::new();
+/// Move a rope with the given number of knots, returning how many positions its tail visited
+fn simulate(input: &Input, length: usize, mut rec: Option<&mut dyn Recorder>) -> usize {
+    let mut rope = Rope::new((0, 0), length);
+    let mut visited = InfiniteGrid::new(false);
+    visited.set(rope.chain[length - 1], true);
+    let mut count = 1;
 
     for motion in input.iter() {
         for _ in 0..motion.count {
             rope.move_head(motion.dir);
-            positions.insert(rope.chain[1]);
+            let seen = visited.get_mut(rope.chain[length - 1]);
+            count += !*seen as usize;
+            *seen = true;
+            record_with(&mut rec, || draw(&rope, &visited));
         }
     }
 
-    Ok(positions.len())
+    count
+}
+
+/// Draw the positions visited by the tail, with the knots on top of them
+fn draw(rope: &Rope, visited: &InfiniteGrid<bool>) -> ((isize, isize), Grid<char>) {
+    let mut world = InfiniteGrid::new('.');
+    for (pos, seen) in visited.points() {
+        world.set(pos, if *seen { '#' } else { '.' });
+    }
+    // draw the head last, so that it's on top
+    for (i, pos) in rope.chain.iter().enumerate().rev() {
+        let c = match i {
+            0 => 'H',
+            _ if rope.chain.len() == 2 => 'T',
+            i => char::from_digit(i as u32, 36).unwrap_or('*'),
+        };
+        world.set(*pos, c);
+    }
+
+    world.to_grid()
+}
+
+fn solve1(input: &Input) -> Result<usize> {
+    Ok(simulate(input, 2, None))
 }
 
 fn solve2(input: &Input) -> Result<usize> {
-    let mut rope = Rope::new((0, 0), 10);
-    let mut positions = std::collections::HashSet::new();
+    Ok(simulate(input, 10, None))
+}
 
-    for motion in input.iter() {
-        for _ in 0..motion.count {
-            rope.move_head(motion.dir);
-            positions.insert(*rope.chain.last().unwrap());
-        }
-    }
+fn record(input: &Input, part: usize, rec: &mut dyn Recorder) -> Result<()> {
+    simulate(input, if part == 1 { 2 } else { 10 }, Some(rec));
+    Ok(())
+}
 
-    Ok(positions.len())
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    (0..rng.count(1..=20 * scale))
+        .map(|_| format!("{} {}\n", rng.pick(&["L", "R", "U", "D"]), rng.count(1..=20)))
+        .collect()
 }
 
-problem!(load_input => Vec<Motion> => (solve1, solve2));
+problem!(load_input => Vec<Motion> => (solve1 -> usize, solve2 -> usize),
+         positive { part1, part2 }, generator generate, record record);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+R 4
+U 4
+L 3
+D 1
+R 4
+D 1
+L 5
+R 2
+";
+
+#[cfg(test)]
+const LARGE_EXAMPLE: &str = "
+R 5
+U 8
+L 8
+D 3
+R 17
+D 10
+L 25
+U 20
+";
+
+examples! {
+    small: EXAMPLE => part1 = 13, part2 = 1;
+    large: LARGE_EXAMPLE => part2 = 36;
+}
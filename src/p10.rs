@@ -2,6 +2,7 @@ use anyhow::Result;
 
 use std::str::FromStr;
 
+use crate::gen::Rng;
 use crate::grid::Grid;
 
 pub enum Insn {
@@ -73,4 +74,189 @@ fn solve2(input: &Input) -> Result<Grid<bool>> {
     Ok(crt)
 }
 
-problem!(crate::util::load_lines => Vec<Insn> => (solve1, solve2));
+/// Generate a program running for at least 240 cycles, which keeps the sprite on the screen
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    let mut out = String::new();
+    let (mut x, mut cycles) = (1, 0);
+    while cycles < 240 + rng.count(0..=20 * scale) {
+        if rng.chance(0.3) {
+            out += "noop\n";
+            cycles += 1;
+        } else {
+            let target = rng.range(1..=39);
+            if target != x {
+                out += &format!("addx {}\n", target - x);
+                x = target;
+                cycles += 2;
+            }
+        }
+    }
+    out
+}
+
+problem!(crate::util::load_lines => Vec<Insn> => (solve1 -> i64, solve2 -> Grid<bool>),
+         generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+addx 15
+addx -11
+addx 6
+addx -3
+addx 5
+addx -1
+addx -8
+addx 13
+addx 4
+noop
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx -35
+addx 1
+addx 24
+addx -19
+addx 1
+addx 16
+addx -11
+noop
+noop
+addx 21
+addx -15
+noop
+noop
+addx -3
+addx 9
+addx 1
+addx -3
+addx 8
+addx 1
+addx 5
+noop
+noop
+noop
+noop
+noop
+addx -36
+noop
+addx 1
+addx 7
+noop
+noop
+noop
+addx 2
+addx 6
+noop
+noop
+noop
+noop
+noop
+addx 1
+noop
+noop
+addx 7
+addx 1
+noop
+addx -13
+addx 13
+addx 7
+noop
+addx 1
+addx -33
+noop
+noop
+noop
+addx 2
+noop
+noop
+noop
+addx 8
+noop
+addx -1
+addx 2
+addx 1
+noop
+addx 17
+addx -9
+addx 1
+addx 1
+addx -3
+addx 11
+noop
+noop
+addx 1
+noop
+addx 1
+noop
+noop
+addx -13
+addx -19
+addx 1
+addx 3
+addx 26
+addx -30
+addx 12
+addx -1
+addx 3
+addx 1
+noop
+noop
+noop
+addx -9
+addx 18
+addx 1
+addx 2
+noop
+noop
+addx 9
+noop
+noop
+noop
+addx -1
+addx 2
+addx -37
+addx 1
+addx 3
+noop
+addx 15
+addx -21
+addx 22
+addx -6
+addx 1
+noop
+addx 2
+addx 1
+noop
+addx -10
+noop
+noop
+addx 20
+addx 1
+addx 2
+addx 2
+addx -6
+addx -11
+noop
+noop
+noop
+";
+
+#[cfg(test)]
+const EXAMPLE_SCREEN: &str = "
+##  ##  ##  ##  ##  ##  ##  ##  ##  ##
+###   ###   ###   ###   ###   ###   ###
+####    ####    ####    ####    ####
+#####     #####     #####     #####
+######      ######      ######      ####
+#######       #######       #######
+";
+
+examples! {
+    example: EXAMPLE => part1 = 13140, part2 = EXAMPLE_SCREEN;
+}
@@ -1,5 +1,9 @@
 use anyhow::Result;
 
+use crate::gen::Rng;
+use crate::parse::{self, Cursor};
+use crate::solution::Inspect;
+
 #[derive(Debug)]
 enum Operand {
     Const(u64),
@@ -32,7 +36,7 @@ impl Op {
 type Item = u64;
 
 #[derive(Debug)]
-struct Monkey {
+pub struct Monkey {
     items: Vec<Item>,
 
     /// Operation
@@ -46,59 +50,69 @@ struct Monkey {
 }
 
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
-    let mut data = String::new();
-    input.read_to_string(&mut data)?;
-
-    fn parse_operation(op: &str) -> Result<Op> {
-        anyhow::ensure!(op.starts_with("new = "));
-        let expr = op.trim_start_matches("new = ");
-        let parts = expr.split(' ').collect::<Vec<_>>();
+    let data = parse::read_all(input)?;
+    let mut cur = Cursor::new(&data);
 
-        anyhow::ensure!(parts.len() == 3);
+    fn parse_operand(line: &mut Cursor) -> Result<Operand> {
+        if line.eat("old") {
+            Ok(Operand::Old)
+        } else {
+            Ok(Operand::Const(line.number()?))
+        }
+    }
 
-        let lhs = match parts[0] {
-            "old" => Operand::Old,
-            n => Operand::Const(n.parse::<u64>()?),
-        };
-        let rhs = match parts[2] {
-            "old" => Operand::Old,
-            n => Operand::Const(n.parse::<u64>()?),
+    fn parse_operation(line: &mut Cursor) -> Result<Op> {
+        line.expect("new = ")?;
+        let lhs = parse_operand(line)?;
+        line.expect(" ")?;
+        let op: fn(Operand, Operand) -> Op = match line.peek() {
+            Some('+') => Op::Add,
+            Some('*') => Op::Mul,
+            _ => return Err(line.error("Invalid operator")),
         };
+        line.next_char();
+        line.expect(" ")?;
+        let rhs = parse_operand(line)?;
+        line.finish()?;
 
-        Ok(match parts[1] {
-            "+" => Op::Add(lhs, rhs),
-            "*" => Op::Mul(lhs, rhs),
-            _ => anyhow::bail!("Invalid operator"),
-        })
+        Ok(op(lhs, rhs))
     }
 
-    fn parse_monkey(desc: &str) -> Result<Monkey> {
-        let lines = desc.lines().collect::<Vec<_>>();
-        let [l_monkey, l_start, l_op, l_test, l_yes, l_no] = lines[..] else {
-            anyhow::bail!("Invalid monkey definition");
+    fn parse_monkey(desc: Cursor) -> Result<Monkey> {
+        let mut lines = desc.lines();
+        let mut next_line = |prefix: &str| -> Result<Cursor> {
+            let mut line = lines.next().ok_or_else(|| {
+                desc.at_end().error(format!("Expected a line starting with '{}'", prefix.trim()))
+            })?;
+            line.expect(prefix)?;
+            Ok(line)
         };
 
-        anyhow::ensure!(l_monkey.starts_with("Monkey "), "Invalid monkey start line");
-        anyhow::ensure!(l_start.starts_with("  Starting items: "), "Invalid monkey items line");
-        anyhow::ensure!(l_op.starts_with("  Operation: "), "Invalid monkey operation line");
-        anyhow::ensure!(l_test.starts_with("  Test: "), "Invalid monkey test line");
-        anyhow::ensure!(l_yes.starts_with("    If true: "), "Invalid monkey true-branch line");
-        anyhow::ensure!(l_no.starts_with("    If false: "), "Invalid monkey false-branch line");
-
-        let items = l_start.trim_start_matches("  Starting items: ")
-                           .split(", ")
-                           .map(|item| item.parse::<u64>().map_err(|e| e.into()))
-                           .collect::<Result<Vec<_>>>()?;
-        let operation = parse_operation(l_op.trim_start_matches("  Operation: "))?;
-        let divisor = l_test.trim_start_matches("  Test: divisible by ")
-                            .parse::<u64>()?;
-        let t_branch = l_yes.trim_start_matches("    If true: throw to monkey ").parse::<usize>()?;
-        let f_branch = l_no.trim_start_matches("    If false: throw to monkey ").parse::<usize>()?;
+        let mut line = next_line("Monkey ")?;
+        line.number::<usize>()?;
+        line.expect(":")?;
+        line.finish()?;
+
+        let mut line = next_line("  Starting items: ")?;
+        let mut items = vec![line.number()?];
+        while line.eat(", ") {
+            items.push(line.number()?);
+        }
+        line.finish()?;
+
+        let operation = parse_operation(&mut next_line("  Operation: ")?)?;
+        let divisor = next_line("  Test: divisible by ")?.parse()?;
+        let t_branch = next_line("    If true: throw to monkey ")?.parse()?;
+        let f_branch = next_line("    If false: throw to monkey ")?.parse()?;
+
+        if let Some(extra) = lines.next() {
+            return Err(extra.error_at(extra.rest(), "Unexpected line after monkey definition"));
+        }
 
         Ok(Monkey { items, operation, divisor, branches: (f_branch, t_branch) })
     }
 
-    data.split("\n\n").map(parse_monkey).collect::<Result<Vec<_>>>()
+    std::iter::from_fn(|| cur.next_block()).map(parse_monkey).collect::<Result<Vec<_>>>()
 }
 
 fn lcm(xs: Vec<u64>) -> u64 {
@@ -169,7 +183,7 @@ impl<'i> Simulation<'i> {
             }
 
             // test worry level
-            let target = if item % monkey.divisor == 0 { monkey.branches.1 }
+            let target = if item.is_multiple_of(monkey.divisor) { monkey.branches.1 }
                          else { monkey.branches.0 };
             self.items[target].push(item);
         }
@@ -191,22 +205,120 @@ impl<'i> Simulation<'i> {
     }
 }
 
-fn solve1(input: &Input) -> Result<usize> {
+fn solve1(input: &Input, params: &Params) -> Result<usize> {
     let mut sim = Simulation::new(input, true);
-    for _ in 0..20 {
+    for _ in 0..params.rounds1 {
         sim.step_round();
     }
 
     Ok(sim.monkey_business())
 }
 
-fn solve2(input: &Input) -> Result<usize> {
+fn solve2(input: &Input, params: &Params) -> Result<usize> {
     let mut sim = Simulation::new(input, false);
-    for _ in 0..10_000 {
+    for _ in 0..params.rounds2 {
         sim.step_round();
     }
 
     Ok(sim.monkey_business())
 }
 
-problem!(load_input => Vec<Monkey> => (solve1, solve2));
+/// Stepping through the simulation a round at a time
+struct Inspector<'i> {
+    sim: Simulation<'i>,
+    round: usize,
+    rounds: usize,
+}
+
+impl Inspect for Inspector<'_> {
+    fn step(&mut self) -> Result<bool> {
+        if self.round == self.rounds {
+            return Ok(false);
+        }
+
+        self.sim.step_round();
+        self.round += 1;
+        Ok(true)
+    }
+
+    fn show(&self) -> String {
+        let mut out = format!("After round {} of {}:\n", self.round, self.rounds);
+        for (idx, (items, inspected)) in self.sim.items.iter().zip(&self.sim.inspected).enumerate() {
+            let items = items.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+            out += &format!("Monkey {} (inspected {}): {}\n", idx, inspected, items.join(", "));
+        }
+        out + &format!("Monkey business: {}", self.sim.monkey_business())
+    }
+}
+
+fn inspect<'i>(input: &'i Input, params: &Params, part: usize) -> Inspector<'i> {
+    let rounds = if part == 1 { params.rounds1 } else { params.rounds2 };
+    Inspector { sim: Simulation::new(input, part == 1), round: 0, rounds }
+}
+
+fn generate(rng: &mut Rng, scale: usize, _params: &Params) -> String {
+    // distinct primes keep the product of the divisors small enough to square
+    let mut divisors = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+    rng.shuffle(&mut divisors);
+
+    let count = rng.count(2..=8);
+    let monkeys = (0..count).map(|idx| {
+        let items = (0..rng.count(1..=2 + scale))
+                   .map(|_| rng.range(40..=99).to_string())
+                   .collect::<Vec<_>>();
+        let operation = match rng.below(3) {
+            0 => "old * old".to_owned(),
+            1 => format!("old * {}", rng.range(2..=19)),
+            _ => format!("old + {}", rng.range(1..=8)),
+        };
+        let mut other = || (idx + rng.count(1..=count - 1)) % count;
+        let (yes, no) = (other(), other());
+        format!("Monkey {}:\n  Starting items: {}\n  Operation: new = {}\n  Test: divisible by {}\n    \
+                 If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+                idx, items.join(", "), operation, divisors[idx], yes, no)
+    });
+    monkeys.collect::<Vec<_>>().join("\n")
+}
+
+problem!(load_input => Vec<Monkey> => (solve1 -> usize, solve2 -> usize), params {
+    /// Number of rounds to simulate in part 1
+    rounds1: usize = 20,
+
+    /// Number of rounds to simulate in part 2
+    rounds2: usize = 10_000,
+}, generator generate, inspect inspect);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+";
+
+examples! {
+    example: EXAMPLE => part1 = 10605, part2 = 2713310158u64;
+}
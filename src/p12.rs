@@ -1,10 +1,12 @@
 use anyhow::Result;
 
-use std::ops::Deref;
+use std::collections::VecDeque;
 
-use crate::grid::Grid;
+use crate::gen::Rng;
+use crate::grid::{search, Grid, GridPoint};
+use crate::record::{record_with, Recorder};
 
-struct Problem {
+pub struct Problem {
     grid: Grid<u8>,
     start: (usize, usize),
     end: (usize, usize),
@@ -17,7 +19,7 @@ fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
                .ok_or_else(|| anyhow::anyhow!("No start position on grid"))?
                .coords();
     let end = grid.find('E').next()
-             .ok_or_else(|| anyhow::anyhow!("No start position on grid"))?
+             .ok_or_else(|| anyhow::anyhow!("No end position on grid"))?
              .coords();
 
     let grid = grid.map(|c| match *c {
@@ -29,45 +31,48 @@ fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
     Ok(Problem { grid, start, end })
 }
 
+/// Whether a step between two cells is possible, going backwards from the end point
+///
+/// All the searches start at the end, so a step here is the reverse of a step on the way up.
+fn climbable(from: GridPoint<'_, u8>, to: GridPoint<'_, u8>) -> bool {
+    *to >= from.saturating_sub(1)
+}
+
+/// Find the shortest route to the end point from the start, heading straight for it
+fn route1(input: &Input) -> Result<Vec<(usize, usize)>> {
+    let start = input.start;
+    let remaining = |p: GridPoint<'_, u8>| p.coords().0.abs_diff(start.0) + p.coords().1.abs_diff(start.1);
+    search::astar(&input.grid, [input.end], |from, to| climbable(from, to).then_some(1),
+                  remaining, |p| p.coords() == start)
+        .path(start)
+        .ok_or_else(|| anyhow::anyhow!("No route from the start position"))
+}
+
+/// Find the shortest route to the end point from any of the lowest positions
+fn route2(input: &Input) -> Result<Vec<(usize, usize)>> {
+    let paths = search::bfs(&input.grid, [input.end], climbable, |p| *p == 0);
+    paths.goal()
+         .and_then(|pos| paths.path(pos))
+         .ok_or_else(|| anyhow::anyhow!("No route from the lowest positions"))
+}
+
 /// Compute how many steps are required to reach the endpoint from any position
-fn steps_into(input: &Input) -> Grid<usize> {
-    let mut steps = Grid::filled_like(&input.grid, usize::MAX - 1);
-    steps.set(input.end, 0);
-
-    let mut changed = true;
-    while changed {
-        changed = false;
-        for point in input.grid.points() {
-            let min_height = point.deref().saturating_sub(1);
-            let l_steps = *steps.get(point.coords()) + 1;
-
-            if let Some(left) = point.left() {
-                let s = steps.get_mut(left.coords());
-                if *left >= min_height && *s > l_steps {
-                    changed = true;
-                    *s = l_steps;
-                }
-            }
-            if let Some(right) = point.right() {
-                let s = steps.get_mut(right.coords());
-                if *right >= min_height && *s > l_steps {
-                    changed = true;
-                    *s = l_steps;
-                }
-            }
-            if let Some(up) = point.up() {
-                let s = steps.get_mut(up.coords());
-                if *up >= min_height && *s > l_steps {
-                    changed = true;
-                    *s = l_steps;
-                }
-            }
-            if let Some(down) = point.down() {
-                let s = steps.get_mut(down.coords());
-                if *down >= min_height && *s > l_steps {
-                    changed = true;
-                    *s = l_steps;
-                }
+///
+/// This is a plain breadth-first search back from the endpoint, kept separate from
+/// [`crate::grid::search`] so that the two can be checked against each other.
+fn steps_into_bfs(input: &Input) -> Grid<Option<usize>> {
+    let mut steps = Grid::filled_like(&input.grid, None);
+    steps.set(input.end, Some(0));
+
+    let mut queue = VecDeque::from([input.end]);
+    while let Some(pos) = queue.pop_front() {
+        let point = input.grid.point(pos);
+        let next_steps = steps.get(pos).map(|s| s + 1);
+
+        for prev in [point.left(), point.right(), point.up(), point.down()].into_iter().flatten() {
+            if climbable(point, prev) && steps.get(prev.coords()).is_none() {
+                steps.set(prev.coords(), next_steps);
+                queue.push_back(prev.coords());
             }
         }
     }
@@ -76,16 +81,72 @@ fn steps_into(input: &Input) -> Grid<usize> {
 }
 
 fn solve1(input: &Input) -> Result<usize> {
-    Ok(*steps_into(input).get(input.start))
+    Ok(route1(input)?.len() - 1)
 }
 
 fn solve2(input: &Input) -> Result<usize> {
+    Ok(route2(input)?.len() - 1)
+}
+
+fn solve1_bfs(input: &Input) -> Result<usize> {
+    steps_into_bfs(input).get(input.start)
+                         .ok_or_else(|| anyhow::anyhow!("No route from the start position"))
+}
+
+fn solve2_bfs(input: &Input) -> Result<usize> {
     // we're computing the inverse here - steps into the end point from anywhere
-    let steps = steps_into(input);
+    let steps = steps_into_bfs(input);
     input.grid.find(0)
-              .map(|p| *steps.get(p.coords()))
+              .filter_map(|p| *steps.get(p.coords()))
               .min()
-              .ok_or_else(|| anyhow::anyhow!("Empty grid"))
+              .ok_or_else(|| anyhow::anyhow!("No route from the lowest positions"))
 }
 
-problem!(load_input => Problem => (solve1, solve2));
+/// Draw the route being followed, a step at a time
+fn record(input: &Input, part: usize, rec: &mut dyn Recorder) -> Result<()> {
+    let route = if part == 1 { route1(input)? } else { route2(input)? };
+    let mut rec = Some(rec);
+
+    // the routes are found backwards from the end point
+    let mut cells = input.grid.clone().map(|h| (b'a' + h) as char);
+    cells.set(input.end, 'E');
+    for pos in route.into_iter().rev() {
+        cells.set(pos, '@');
+        record_with(&mut rec, || ((0, 0), cells.clone()));
+        cells.set(pos, '#');
+    }
+    Ok(())
+}
+
+/// Generate a random map, with a path from the start climbing one letter per step to the end
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    let width = rng.count(26..=26 + 5 * scale);
+    let height = rng.count(1..=2 + 2 * scale);
+    let mut rows = (0..height).map(|_| rng.word(width).into_bytes()).collect::<Vec<_>>();
+
+    let (x, y) = (rng.below(width - 25), rng.below(height));
+    for (offset, height) in (b'a'..=b'z').enumerate() {
+        rows[y][x + offset] = height;
+    }
+    rows[y][x] = b'S';
+    rows[y][x + 25] = b'E';
+
+    rows.into_iter().map(|row| String::from_utf8(row).unwrap() + "\n").collect()
+}
+
+problem!(load_input => Problem => (solve1 -> usize, solve2 -> usize),
+         variants { part1: bfs = solve1_bfs, part2: bfs = solve2_bfs },
+         positive { part1, part2 }, generator generate, record record);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi
+";
+
+examples! {
+    example: EXAMPLE => part1 = 31, part2 = 29;
+}
@@ -1,61 +1,53 @@
-use anyhow::{Result, anyhow};
+use anyhow::Result;
+
+use crate::gen::Rng;
+use crate::parse::{self, Cursor};
 
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
-    let mut data = String::new();
-    input.read_to_string(&mut data)?;
+    let data = parse::read_all(input)?;
+    let mut cur = Cursor::new(&data);
 
-    fn parse_packet<I: Iterator<Item=char>>(chars: &mut std::iter::Peekable<I>) -> Result<Packet> {
-        match chars.next().ok_or_else(|| anyhow!("Invalid packet"))? {
-            '[' => {
-                let mut items = Vec::new();
-                loop {
-                    match chars.peek() {
-                        Some(',') => {
-                            chars.next();
-                        }
-                        Some(']') => {
-                            chars.next();
-                            break;
-                        }
-                        _ => {
-                            items.push(parse_packet(chars)?);
-                        }
-                    }
-                }
+    fn parse_packet(line: &mut Cursor) -> Result<Packet> {
+        if !line.eat("[") {
+            return Ok(Packet::N(line.number()?));
+        }
 
-                Ok(Packet::L(items))
-            },
-            n => {
-                let mut n = n.to_digit(10).ok_or_else(|| anyhow!("Invalid packet digit"))?;
-                loop {
-                    match chars.peek().ok_or_else(|| anyhow!("Invalid packet format"))? {
-                        c if c.is_ascii_digit() => {
-                            let m = c.to_digit(10).unwrap();
-                            n = (n*10) + m;
-                            chars.next();
-                        }
-                        _ => {
-                            break;
-                        }
-                    }
+        let mut items = Vec::new();
+        if !line.eat("]") {
+            loop {
+                items.push(parse_packet(line)?);
+                if line.eat("]") {
+                    break;
                 }
-
-                Ok(Packet::N(n as u8))
-            },
+                line.expect(",")?;
+            }
         }
+
+        Ok(Packet::L(items))
     }
 
-    data.split("\n\n")
+    std::iter::from_fn(|| cur.next_block())
         .map(|group| {
-            let xs = group.split_once('\n').ok_or_else(|| anyhow!("Invalid packet group"))?;
-            Ok((parse_packet(&mut xs.0.chars().peekable())?,
-                parse_packet(&mut xs.1.chars().peekable())?))
+            let mut lines = group.lines();
+            let mut packet = || {
+                let mut line = lines.next()
+                              .ok_or_else(|| group.at_end().error("Expected another packet"))?;
+                let packet = parse_packet(&mut line)?;
+                line.finish()?;
+                Ok::<_, anyhow::Error>(packet)
+            };
+            let pair = (packet()?, packet()?);
+
+            if let Some(extra) = lines.next() {
+                return Err(extra.error_at(extra.rest(), "Expected a pair of packets"));
+            }
+            Ok(pair)
         })
         .collect::<Result<Vec<_>>>()
 }
 
 #[derive(Debug, PartialEq, Eq)]
-enum Packet {
+pub enum Packet {
     L(Vec<Self>),
     N(u8),
 }
@@ -119,4 +111,69 @@ fn solve2(input: &Input) -> Result<usize> {
     Ok(loc_d0 * loc_d1)
 }
 
-problem!(load_input => Vec<(Packet, Packet)> => (solve1, solve2));
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    fn packet(rng: &mut Rng, depth: usize) -> Packet {
+        Packet::L((0..rng.count(0..=4))
+                  .map(|_| if depth < 3 && rng.chance(0.3) {
+                      packet(rng, depth + 1)
+                  } else {
+                      Packet::N(rng.range(0..=10) as u8)
+                  })
+                  .collect())
+    }
+
+    fn format(packet: &Packet) -> String {
+        match packet {
+            Packet::N(n) => n.to_string(),
+            Packet::L(items) => format!("[{}]", items.iter().map(format).collect::<Vec<_>>().join(",")),
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for idx in 0..rng.count(1..=3 * scale) {
+        let (mut a, mut b) = (packet(rng, 0), packet(rng, 0));
+        // make sure at least the first pair is in the right order
+        while idx == 0 && a == b {
+            b = packet(rng, 0);
+        }
+        if idx == 0 && a > b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        pairs.push(format!("{}\n{}\n", format(&a), format(&b)));
+    }
+    pairs.join("\n")
+}
+
+problem!(load_input => Vec<(Packet, Packet)> => (solve1 -> usize, solve2 -> usize),
+         generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+[1,1,3,1,1]
+[1,1,5,1,1]
+
+[[1],[2,3,4]]
+[[1],4]
+
+[9]
+[[8,7,6]]
+
+[[4,4],4,4]
+[[4,4],4,4,4]
+
+[7,7,7,7]
+[7,7,7]
+
+[]
+[3]
+
+[[[]]]
+[[]]
+
+[1,[2,[3,[4,[5,6,7]]]],8,9]
+[1,[2,[3,[4,[5,6,0]]]],8,9]
+";
+
+examples! {
+    example: EXAMPLE => part1 = 13, part2 = 140;
+}
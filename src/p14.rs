@@ -1,6 +1,8 @@
 use anyhow::Result;
 
-use crate::grid::Grid;
+use crate::gen::Rng;
+use crate::grid::{Grid, InfiniteGrid};
+use crate::record::{record_with, Recorder};
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 enum Cell {
@@ -10,120 +12,181 @@ enum Cell {
 }
 
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
-    let paths = crate::util::read_lines(input, |line| {
-        line.split(" -> ")
-            .map(|p| {
-                let (x,y) = p.split_once(',')
-                             .ok_or_else(|| anyhow::anyhow!("Invalid coordinates"))?;
-                Ok((x.parse::<usize>()?, y.parse::<usize>()?))
-            })
-            .collect::<Result<Vec<_>>>()
-    })?;
+    let paths = crate::util::read_lines(input, |mut line| {
+        let mut path = Vec::<(isize, isize)>::new();
+        loop {
+            let mark = line;
+            let x = line.number()?;
+            line.expect(",")?;
+            let y = line.number()?;
+            if path.last().is_some_and(|prev| prev.0 != x && prev.1 != y) {
+                return Err(line.error_at(line.since(mark), "Paths can't run diagonally"));
+            }
+            path.push((x, y));
 
-    let width = paths.iter().map(|p| p.iter().map(|t| t.0).max().unwrap()).max().unwrap() + 200;
-    let height = paths.iter().map(|p| p.iter().map(|t| t.1).max().unwrap()).max().unwrap() + 2;
+            if line.is_empty() {
+                break Ok(path);
+            }
+            line.expect(" -> ")?;
+        }
+    })?;
+    anyhow::ensure!(!paths.is_empty(), "No rock paths in input");
 
-    let mut cells = Grid::filled(width, height, Cell::Empty);
+    let mut cells = InfiniteGrid::new(Cell::Empty);
     for p in paths {
-        let mut pos = p[0];
-        for next in &p[1..] {
-            if pos.0 < next.0 {
-                assert_eq!(pos.1, next.1);
-                for x in pos.0..=next.0 {
-                    cells.set((x, pos.1), Cell::Wall);
-                }
-            } else if pos.0 > next.0 {
-                assert_eq!(pos.1, next.1);
-                for x in next.0..=pos.0 {
-                    cells.set((x, pos.1), Cell::Wall);
-                }
-            } else if pos.1 < next.1 {
-                assert_eq!(pos.0, next.0);
-                for y in pos.1..=next.1 {
-                    cells.set((pos.0, y), Cell::Wall);
-                }
-            } else if pos.1 > next.1 {
-                assert_eq!(pos.0, next.0);
-                for y in next.1..=pos.1 {
-                    cells.set((pos.0, y), Cell::Wall);
+        for (a, b) in p.iter().zip(&p[1..]) {
+            for x in a.0.min(b.0)..=a.0.max(b.0) {
+                for y in a.1.min(b.1)..=a.1.max(b.1) {
+                    cells.set((x, y), Cell::Wall);
                 }
             }
-
-            pos = *next;
         }
     }
 
-    Ok(Problem { cells })
+    // the lowest row sand can come to rest on, just above the floor
+    let (_, lowest) = cells.bounds().ok_or_else(|| anyhow::anyhow!("Rock paths don't cover anything"))?;
+    let depth = lowest.1 + 1;
+    anyhow::ensure!(depth > 0, "Rock can't be above the sand source");
+
+    Ok(Problem { cells, depth })
 }
 
-struct Problem {
-    cells: Grid<Cell>,
+pub struct Problem {
+    cells: InfiniteGrid<Cell>,
+
+    /// Row beneath the lowest rock
+    depth: isize,
 }
 
 /// Compute the landing point of the next dropped sand unit
 ///
-/// If the sand will never land, return `None`.
-fn landing_point(state: &Grid<Cell>) -> Result<(usize, usize), (usize, usize)> {
+/// Sand which reaches the row beneath the lowest rock will fall forever, so this returns `Err`
+/// with the position where it got there.
+fn landing_point(state: &InfiniteGrid<Cell>, depth: isize) -> Result<(isize, isize), (isize, isize)> {
     let mut pos = (500, 0);
 
-    while state.try_get(pos).is_some() {
-        // try down
-        let Some(below) = state.try_get((pos.0, pos.1+1)) else { return Err(pos); };
-        if *below == Cell::Empty {
-            pos.1 += 1;
-            continue;
-        }
-
-        // try left
-        let Some(below) = state.try_get((pos.0-1, pos.1+1)) else { unreachable!() };
-        if *below == Cell::Empty {
-            pos.1 += 1;
-            pos.0 -= 1;
-            continue;
+    'fall: loop {
+        if pos.1 == depth {
+            return Err(pos);
         }
 
-        let Some(below) = state.try_get((pos.0+1, pos.1+1)) else { unreachable!() };
-        if *below == Cell::Empty {
-            pos.1 += 1;
-            pos.0 += 1;
-            continue;
+        // try down, then down-left, then down-right
+        for dx in [0, -1, 1] {
+            let below = (pos.0 + dx, pos.1 + 1);
+            if *state.get(below) == Cell::Empty {
+                pos = below;
+                continue 'fall;
+            }
         }
 
         return Ok(pos);
     }
-
-    unreachable!()
 }
 
-fn solve1(input: &Input) -> Result<usize> {
+/// Pour sand until it stops, returning how many units came to rest
+///
+/// Without a floor, sand stops once it starts falling into the abyss. With one, sand which would
+/// fall past the lowest rock lands on the floor instead.
+fn pour(input: &Input, floor: bool, mut rec: Option<&mut dyn Recorder>) -> usize {
     let mut state = input.cells.clone();
-    while let Ok(pos) = landing_point(&state) {
+    let view = view(input, floor);
+    let mut count = 0;
+    // sand normally falls into the abyss first, but it could also fill up to the source
+    while *state.get((500, 0)) == Cell::Empty {
+        let pos = match landing_point(&state, input.depth) {
+            Ok(pos) => pos,
+            Err(pos) if floor => pos,
+            Err(_) => break,
+        };
         let c = state.get_mut(pos);
         assert_eq!(*c, Cell::Empty);
         *c = Cell::Sand;
+        count += 1;
+        record_with(&mut rec, || draw(&state, input.depth, view.clone()));
     }
 
-    Ok(state.cells().filter(|c| **c == Cell::Sand).count())
+    count
+}
+
+/// Columns where sand can end up, for drawing
+fn view(input: &Input, floor: bool) -> std::ops::RangeInclusive<isize> {
+    let ((min, _), (max, _)) = input.cells.bounds().unwrap();
+    if floor {
+        // sand piles up in a triangle down to the floor
+        min.min(500 - input.depth)..=max.max(500 + input.depth)
+    } else {
+        min - 1..=max + 1
+    }
+}
+
+fn draw(state: &InfiniteGrid<Cell>, depth: isize, cols: std::ops::RangeInclusive<isize>)
+    -> ((isize, isize), Grid<char>)
+{
+    let left = *cols.start();
+    let grid = Grid::from_fn(cols.count(), depth as usize + 1, |x, y| {
+        let pos = (left + x as isize, y as isize);
+        match state.get(pos) {
+            Cell::Empty if pos == (500, 0) => '+',
+            Cell::Empty => '.',
+            Cell::Wall => '#',
+            Cell::Sand => 'o',
+        }
+    });
+    ((left, 0), grid)
+}
+
+fn solve1(input: &Input) -> Result<usize> {
+    Ok(pour(input, false, None))
 }
 
 fn solve2(input: &Input) -> Result<usize> {
-    let mut state = input.cells.clone();
-    while *state.get((500, 0)) == Cell::Empty {
-        match landing_point(&state) {
-            Ok(pos) => {
-                let c = state.get_mut(pos);
-                assert_eq!(*c, Cell::Empty);
-                *c = Cell::Sand;
-            }
-            Err(pos) => {
-                let c = state.get_mut(pos);
-                assert_eq!(*c, Cell::Empty);
-                *c = Cell::Sand;
+    Ok(pour(input, true, None))
+}
+
+fn record(input: &Input, part: usize, rec: &mut dyn Recorder) -> Result<()> {
+    pour(input, part == 2, Some(rec));
+    Ok(())
+}
+
+/// Generate rock paths around the sand source, with one directly below it so some sand settles
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    // keep everything shallow, as the pile of sand in part 2 grows with the square of the depth
+    let spread = 3 * scale as i64 + 10;
+    let depth = (5 * scale as i64 + 10).min(150);
+
+    let mut paths = Vec::new();
+    let top = rng.range(1..=depth / 2);
+    // this has to stick out on both sides, and be above everything else, or the first sand could
+    // just slide off into the abyss
+    paths.push(format!("{},{} -> {},{}", rng.range(500 - spread..=499), top, rng.range(501..=500 + spread), top));
+
+    for _ in 0..rng.count(0..=2 * scale) {
+        let (mut x, mut y) = (rng.range(500 - spread..=500 + spread), rng.range(top + 1..=depth));
+        let mut points = vec![format!("{},{}", x, y)];
+        for step in 0..rng.count(1..=4) {
+            if step % 2 == 0 {
+                x = (x + rng.range(-6..=6)).clamp(500 - spread, 500 + spread);
+            } else {
+                y = (y + rng.range(-6..=6)).clamp(top + 1, depth);
             }
+            points.push(format!("{},{}", x, y));
         }
+        paths.push(points.join(" -> "));
     }
 
-    Ok(state.cells().filter(|c| **c == Cell::Sand).count())
+    rng.shuffle(&mut paths);
+    paths.iter().map(|p| format!("{}\n", p)).collect()
 }
 
-problem!(load_input => Problem => (solve1, solve2));
+problem!(load_input => Problem => (solve1 -> usize, solve2 -> usize),
+         generator generate, record record);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9
+";
+
+examples! {
+    example: EXAMPLE => part1 = 24, part2 = 93;
+}
@@ -2,6 +2,8 @@ use anyhow::Result;
 
 use std::collections::BinaryHeap;
 
+use crate::gen::Rng;
+
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
     crate::util::read_lines_regex(
         input,
@@ -25,7 +27,7 @@ struct Span {
 }
 
 #[derive(Debug)]
-struct Reading {
+pub struct Reading {
     /// Sensor position
     position: (isize, isize),
 
@@ -184,18 +186,17 @@ fn beacon_spans(input: &Input, min: isize, max: isize) -> Vec<Vec<Span>> {
 /// of relevant beacons at each step. When the target row is reached, each relevant beacon is
 /// evaluated to give a span of X-coordinates that it covers. Overlapping spans are then merged, and
 /// the final result is computed as the sum of all span widths.
-fn solve1(input: &Input) -> Result<usize> {
-    const TARGET_ROW: isize = 2_000_000;
-    //const TARGET_ROW: isize = 10;
+fn solve1(input: &Input, params: &Params) -> Result<usize> {
+    let target_row = params.target_row;
 
     anyhow::ensure!(!input.is_empty());
 
-    let spans = beacon_spans(input, TARGET_ROW, TARGET_ROW+1).swap_remove(0);
+    let spans = beacon_spans(input, target_row, target_row+1).swap_remove(0);
 
     // Remove any beacons within this row
     let mut beacons = input.iter()
                      .map(|r| r.beacon)
-                     .filter(|b| b.1 == TARGET_ROW)
+                     .filter(|b| b.1 == target_row)
                      .collect::<Vec<_>>();
     beacons.sort();
     beacons.dedup();
@@ -205,17 +206,16 @@ fn solve1(input: &Input) -> Result<usize> {
     Ok(spans.iter().map(|span| (span.end - span.start) as usize).sum::<usize>() - beacons)
 }
 
-fn solve2(input: &Input) -> Result<usize> {
-    const BOUND: isize = 4_000_000;
-    //const BOUND: isize = 20;
+fn solve2(input: &Input, params: &Params) -> Result<usize> {
+    let bound = params.bound;
 
     anyhow::ensure!(!input.is_empty());
 
     let mut out_coords = Vec::new();
-    for (row, spans) in beacon_spans(input, 0, BOUND+1).iter().enumerate() {
+    for (row, spans) in beacon_spans(input, 0, bound+1).iter().enumerate() {
         if spans.len() == 1 {
             // does the row's single span cover all cells?
-            if spans[0].start <= 0 && spans[0].end > BOUND {
+            if spans[0].start <= 0 && spans[0].end > bound {
                 continue;
             }
         }
@@ -233,8 +233,8 @@ fn solve2(input: &Input) -> Result<usize> {
             }
         }
 
-        if x <= BOUND {
-            for x in x..=BOUND {
+        if x <= bound {
+            for x in x..=bound {
                 out_coords.push((x as usize, row));
             }
         }
@@ -245,4 +245,68 @@ fn solve2(input: &Input) -> Result<usize> {
     Ok(out_coords[0].0 * 4_000_000 + out_coords[0].1)
 }
 
-problem!(load_input => Vec<Reading> => (solve1, solve2));
+/// Generate sensors which leave exactly one cell uncovered within the bounds
+///
+/// Four large sensors surround the distress beacon diagonally, with the edges of their ranges
+/// meeting around it. The rest are noise, which never reach the distress beacon.
+fn generate(rng: &mut Rng, scale: usize, params: &Params) -> String {
+    let bound = params.bound as i64;
+    let distress = (rng.range(1..=bound), rng.range(1..=bound));
+    let k = bound + 1;
+
+    let mut readings = Vec::new();
+    for (dx, dy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+        let sensor = (distress.0 + dx * k, distress.1 + dy * k);
+        readings.push((sensor, (distress.0 + dx, distress.1)));
+    }
+
+    for _ in 0..rng.count(0..=scale) {
+        let sensor = (rng.range(0..=bound), rng.range(0..=bound));
+        let dist = (sensor.0 - distress.0).abs() + (sensor.1 - distress.1).abs();
+        if dist <= 1 {
+            continue;
+        }
+
+        let radius = rng.range(1..=(dist - 1).min(bound / 2).max(1));
+        let dx = rng.range(-radius..=radius);
+        let dy = (radius - dx.abs()) * if rng.chance(0.5) { 1 } else { -1 };
+        readings.push((sensor, (sensor.0 + dx, sensor.1 + dy)));
+    }
+
+    rng.shuffle(&mut readings);
+    readings.iter()
+            .map(|((sx, sy), (bx, by))| {
+                format!("Sensor at x={}, y={}: closest beacon is at x={}, y={}\n", sx, sy, bx, by)
+            })
+            .collect()
+}
+
+problem!(load_input => Vec<Reading> => (solve1 -> usize, solve2 -> usize), params {
+    /// Row to count covered cells in for part 1
+    target_row: isize = 2_000_000,
+
+    /// Largest coordinate the distress beacon can have in part 2
+    bound: isize = 4_000_000,
+}, generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3
+";
+
+examples! {
+    example: EXAMPLE, params(target_row = 10, bound = 20) => part1 = 26, part2 = 56000011;
+}
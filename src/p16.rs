@@ -1,9 +1,11 @@
 use anyhow::Result;
 
-use std::collections::VecDeque;
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::gen::Rng;
 
 #[derive(Debug)]
-struct Valve {
+pub struct Valve {
     flow: usize,
     neighbors: Vec<usize>,
 }
@@ -123,7 +125,7 @@ fn max_for_subset(input: &Input, apsp: &[Vec<usize>], subset: u64, t_max: usize)
             paths: &'p [Vec<usize>],
             valves: &'p [Valve],
         ) -> impl Iterator<Item=Self> + 'p {
-            (0..valves.len()).into_iter()
+            (0..valves.len())
                              .filter(|idx| (paths[self.pos][*idx] + 1) < self.t && // reachable?
                                            self.is_avail(*idx)) // not already set?
                              .map(move |idx| {
@@ -225,4 +227,135 @@ fn solve2(input: &Input) -> Result<usize> {
     Ok(res)
 }
 
-problem!(load_input => (usize, Vec<Valve>) => (solve1, solve2));
+/// Best pressure released by opening each set of working valves, found by walking through the
+/// tunnels a minute at a time
+///
+/// Sets are bitmasks over the valves with some flow, in input order. Unlike [`max_for_subset`],
+/// this never rules out a route as hopeless, so it's a slow but simple check of the main solvers.
+fn released_by_set(input: &Input, t_max: usize) -> Result<Vec<usize>> {
+    let (start, valves) = (input.0, &input.1);
+    let working = valves.iter().enumerate()
+                 .filter(|(_, v)| v.flow != 0)
+                 .map(|t| t.0)
+                 .collect::<Vec<_>>();
+    anyhow::ensure!(working.len() < 20, "Too many working valves for a brute-force search");
+
+    // best score for each position and set of opened valves, at `pos * sets + opened`
+    let sets = 1 << working.len();
+    let mut best = vec![None; valves.len() * sets];
+    best[start * sets] = Some(0);
+    for t in (1..=t_max).rev() {
+        let mut next = vec![None; best.len()];
+        for (idx, score) in best.iter().enumerate() {
+            let Some(score) = *score else { continue };
+            let (pos, opened) = (idx / sets, idx % sets);
+            let mut reach = |pos: usize, opened: usize, score: usize| {
+                let slot: &mut Option<usize> = &mut next[pos * sets + opened];
+                *slot = (*slot).max(Some(score));
+            };
+
+            reach(pos, opened, score);
+            for n in &valves[pos].neighbors {
+                reach(*n, opened, score);
+            }
+            if let Some(bit) = working.iter().position(|w| *w == pos) {
+                if opened & (1 << bit) == 0 {
+                    reach(pos, opened | (1 << bit), score + valves[pos].flow * (t - 1));
+                }
+            }
+        }
+        best = next;
+    }
+
+    let mut by_set = (0..sets)
+                    .map(|opened| (0..valves.len()).filter_map(|pos| best[pos * sets + opened]).max())
+                    .collect::<Vec<_>>();
+
+    // opening more valves never hurts, since any of them can be left shut
+    for bit in 0..working.len() {
+        for opened in 0..sets {
+            if opened & (1 << bit) != 0 {
+                by_set[opened] = by_set[opened].max(by_set[opened ^ (1 << bit)]);
+            }
+        }
+    }
+    Ok(by_set.into_iter().map(|s| s.unwrap_or(0)).collect())
+}
+
+fn solve1_brute(input: &Input) -> Result<usize> {
+    Ok(*released_by_set(input, 30)?.last().unwrap())
+}
+
+fn solve2_brute(input: &Input) -> Result<usize> {
+    // we open one set of valves and the elephant opens the rest
+    let by_set = released_by_set(input, 26)?;
+    let all = by_set.len() - 1;
+    Ok((0..by_set.len()).map(|us| by_set[us] + by_set[all ^ us]).max().unwrap())
+}
+
+/// Generate a connected network of valves, with a working valve next to `AA`
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    let count = rng.count(2..=(5 + 5 * scale).min(60));
+    let mut names = ('A'..='Z').flat_map(|a| ('A'..='Z').map(move |b| format!("{}{}", a, b)))
+                               .skip(1)
+                               .collect::<Vec<_>>();
+    rng.shuffle(&mut names);
+    names.insert(0, "AA".to_owned());
+
+    // a random tree keeps it connected, with valve 1 attached to AA
+    let mut tunnels = vec![BTreeSet::new(); count];
+    let mut link = |a: usize, b: usize| {
+        tunnels[a].insert(b);
+        tunnels[b].insert(a);
+    };
+    for idx in 1..count {
+        link(idx, rng.below(idx));
+    }
+    for _ in 0..rng.count(0..=count / 3) {
+        let (a, b) = (rng.below(count), rng.below(count));
+        if a != b {
+            link(a, b);
+        }
+    }
+
+    let mut flows = vec![0; count];
+    flows[1] = rng.range(1..=25);
+    for _ in 1..rng.count(1..=(2 + scale).min(15).min(count - 1)) {
+        flows[rng.count(1..=count - 1)] = rng.range(1..=25);
+    }
+
+    let mut lines = (0..count).map(|idx| {
+        let others = tunnels[idx].iter().map(|t| names[*t].as_str()).collect::<Vec<_>>();
+        let (tunnel, valve) = if others.len() == 1 {
+            ("tunnel leads", "valve")
+        } else {
+            ("tunnels lead", "valves")
+        };
+        format!("Valve {} has flow rate={}; {} to {} {}\n",
+                names[idx], flows[idx], tunnel, valve, others.join(", "))
+    }).collect::<Vec<_>>();
+    rng.shuffle(&mut lines);
+    lines.concat()
+}
+
+problem!(load_input => (usize, Vec<Valve>) => (solve1 -> usize, solve2 -> usize),
+         variants { part1: brute = solve1_brute, part2: brute = solve2_brute },
+         generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II
+";
+
+examples! {
+    example: EXAMPLE => part1 = 1651, part2 = 1707;
+}
@@ -1,24 +1,33 @@
 use anyhow::Result;
 
+use crate::gen::Rng;
+use crate::grid::Grid;
+use crate::parse::{self, Cursor};
+use crate::record::{record_with, Recorder};
+
 #[derive(Copy, Clone, Debug)]
-enum Dir {
+pub enum Dir {
     Left, Right
 }
 
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
-    let mut s = String::new();
-    input.read_to_string(&mut s)?;
-    let mut lines = s.lines();
-    let line = lines.next().ok_or_else(|| anyhow::anyhow!("Input is empty"))?;
-    anyhow::ensure!(lines.next().is_none(), "Input has extra newlines");
+    let data = parse::read_all(input)?;
+    let mut lines = Cursor::new(&data).lines();
+    let mut line = lines.next().ok_or_else(|| anyhow::anyhow!("Input is empty"))?;
+    if let Some(extra) = lines.next() {
+        return Err(extra.error_at(extra.rest(), "Input has extra lines"));
+    }
 
-    line.chars()
-        .map(|c| match c {
-            '>' => Ok(Dir::Right),
-            '<' => Ok(Dir::Left),
-            c   => Err(anyhow::anyhow!("Invalid char '{}' in input", c)),
-        })
-        .collect()
+    let mut out = Vec::new();
+    while let Some(c) = line.peek() {
+        out.push(match c {
+            '>' => Dir::Right,
+            '<' => Dir::Left,
+            c   => return Err(line.error(format!("Invalid char '{}' in input", c))),
+        });
+        line.next_char();
+    }
+    Ok(out)
 }
 
 struct Shape {
@@ -55,6 +64,10 @@ const ROCKS: &[Shape] = &[
 /// bottom row and go up from there.
 ///
 /// The position of a shape is considered to be the location of its lower-right corner.
+///
+/// This isn't a [`BitGrid`](crate::grid::BitGrid), which has a fixed height and a whole word per
+/// row. The chamber is only seven cells wide but keeps growing upwards, so a byte per row packs it
+/// more tightly and lets the cycle search compare the top rows as plain slices.
 struct Board {
     /// Rows of this board
     ///
@@ -113,57 +126,97 @@ impl Board {
     }
 }
 
-/// Simulate the given input for a provided number of rocks
-///
-/// Return the height of the board after simulation completes.
-fn simulate_floyd(input: &Input, rocks: usize) -> Result<usize> {
-    const CONTEXT: usize = 256;
+/// Board state between rock drops
+#[derive(Clone)]
+struct State {
+    board: Board,
+    t: usize,
+    rock_idx: usize,
+}
 
-    #[derive(Clone)]
-    struct State {
-        board: Board,
-        t: usize,
-        rock_idx: usize,
-    }
-
-    fn step(input: &Input, mut s: State) -> State {
-        // drop the rock
-        let rock = &ROCKS[s.rock_idx];
-        s.rock_idx = (s.rock_idx + 1) % ROCKS.len();
-        let mut pos = s.board.drop_pos(rock.width);
-        loop {
-            // try to push in a direction
-            let cand_pos = match input[s.t] {
-                Dir::Left => {
-                    ((pos.0 + 1).min(7 - rock.width), pos.1)
-                }
-                Dir::Right => {
-                    (pos.0.saturating_sub(1), pos.1)
-                }
-            };
-            if !s.board.collides(rock, cand_pos) {
-                pos = cand_pos;
-            }
-            s.t = (s.t + 1) % input.len();
+/// Drop the next rock, returning the state once it has landed
+fn step(input: &Input, s: State) -> State {
+    step_recorded(input, s, &mut None)
+}
 
-            // try to fall one step
-            if pos.1 == 0 {
-                // hit the floor - rock lands
-                break;
+/// Drop the next rock, drawing a frame after each push and fall
+fn step_recorded(input: &Input, mut s: State, rec: &mut Option<&mut dyn Recorder>) -> State {
+    // drop the rock
+    let rock = &ROCKS[s.rock_idx];
+    s.rock_idx = (s.rock_idx + 1) % ROCKS.len();
+    let mut pos = s.board.drop_pos(rock.width);
+    loop {
+        // try to push in a direction
+        let cand_pos = match input[s.t] {
+            Dir::Left => {
+                ((pos.0 + 1).min(7 - rock.width), pos.1)
             }
-            let cand_pos = (pos.0, pos.1 - 1);
-            if s.board.collides(rock, cand_pos) {
-                break;
+            Dir::Right => {
+                (pos.0.saturating_sub(1), pos.1)
             }
+        };
+        if !s.board.collides(rock, cand_pos) {
             pos = cand_pos;
         }
+        s.t = (s.t + 1) % input.len();
+        record_with(rec, || draw(&s.board, rock, pos));
 
-        // rock landed
-        s.board.place(rock, pos);
-
-        s
+        // try to fall one step
+        if pos.1 == 0 {
+            // hit the floor - rock lands
+            break;
+        }
+        let cand_pos = (pos.0, pos.1 - 1);
+        if s.board.collides(rock, cand_pos) {
+            break;
+        }
+        pos = cand_pos;
+        record_with(rec, || draw(&s.board, rock, pos));
     }
 
+    // rock landed
+    s.board.place(rock, pos);
+
+    s
+}
+
+/// Draw the top of the board with a falling rock, as in the puzzle's examples
+///
+/// The view follows the rock and the top of the tower, so every frame is the same size.
+fn draw(board: &Board, rock: &Shape, pos: (usize, usize)) -> ((isize, isize), Grid<char>) {
+    const VIEW: usize = 30;
+
+    let top = board.rows.len().max(pos.1 + rock.height);
+    let bottom = top.saturating_sub(VIEW);
+    let grid = Grid::from_fn(9, VIEW + 1, |x, row| {
+        let edge = x == 0 || x == 8;
+        let Some(y) = (bottom + VIEW).checked_sub(row + 1) else {
+            return if edge { '+' } else { '-' };
+        };
+        if edge {
+            return '|';
+        }
+
+        let bit = 1u8 << (7 - x);
+        let in_rock = (pos.1..pos.1 + rock.height).contains(&y)
+                      && rock.lines[3 - (y - pos.1)] << pos.0 & bit != 0;
+        if board.rows.get(y).is_some_and(|r| r & bit != 0) {
+            '#'
+        } else if in_rock {
+            '@'
+        } else {
+            '.'
+        }
+    });
+    ((0, 0), grid)
+}
+
+/// Simulate the given input for a provided number of rocks
+///
+/// Return the height of the board after simulation completes.
+fn simulate_floyd(input: &Input, rocks: usize) -> Result<usize> {
+    const CONTEXT: usize = 256;
+
     fn board_compare(a: &State, b: &State) -> bool {
         let a_ctx = a.board.rows.len().saturating_sub(CONTEXT);
         let b_ctx = b.board.rows.len().saturating_sub(CONTEXT);
@@ -244,6 +297,23 @@ fn simulate_floyd(input: &Input, rocks: usize) -> Result<usize> {
     Ok(x.board.rows.len() + rep_height)
 }
 
+/// Simulate the given input for a provided number of rocks, one rock at a time
+///
+/// This is only practical for small numbers of rocks, but serves as a check on the cycle
+/// detection in [`simulate_floyd`].
+fn simulate(input: &Input, rocks: usize) -> Result<usize> {
+    let mut x = State {
+        board: Board::new(),
+        t: 0,
+        rock_idx: 0,
+    };
+    for _ in 0..rocks {
+        x = step(input, x);
+    }
+
+    Ok(x.board.rows.len())
+}
+
 fn solve1(input: &Input) -> Result<usize> {
     simulate_floyd(input, 2022)
 }
@@ -252,4 +322,42 @@ fn solve2(input: &Input) -> Result<usize> {
     simulate_floyd(input, 1000000000000)
 }
 
-problem!(load_input => Vec<Dir> => (solve1, solve2));
+fn solve1_simulate(input: &Input) -> Result<usize> {
+    simulate(input, 2022)
+}
+
+fn record(input: &Input, part: usize, rec: &mut dyn Recorder) -> Result<()> {
+    // part 2 is the same simulation, but for far more rocks than could ever be drawn
+    anyhow::ensure!(part == 1, "Only part 1 can be recorded");
+    let mut rec = Some(rec);
+    let mut x = State {
+        board: Board::new(),
+        t: 0,
+        rock_idx: 0,
+    };
+    for _ in 0..2022 {
+        x = step_recorded(input, x, &mut rec);
+    }
+    Ok(())
+}
+
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    let mut out = (0..rng.count(1..=40 * scale))
+                 .map(|_| if rng.chance(0.5) { '<' } else { '>' })
+                 .collect::<String>();
+    out.push('\n');
+    out
+}
+
+problem!(load_input => Vec<Dir> => (solve1 -> usize, solve2 -> usize),
+         variants { part1: simulate = solve1_simulate },
+         positive { part1, part2 }, generator generate, record record);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+>>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>
+";
+
+examples! {
+    example: EXAMPLE => part1 = 3068, part2 = 1514285714288u64;
+}
@@ -1,7 +1,12 @@
 use anyhow::Result;
 
+use std::collections::HashSet;
+
+use crate::gen::Rng;
+use crate::grid::Grid3;
+
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
-struct Point([isize; 3]);
+pub struct Point([isize; 3]);
 
 impl std::str::FromStr for Point {
     type Err = anyhow::Error;
@@ -15,67 +20,70 @@ impl std::str::FromStr for Point {
     }
 }
 
-fn solve1(input: &Input) -> Result<usize> {
-    let data = input.iter().cloned().collect::<std::collections::HashSet<_>>();
-    let sides = input.iter().cloned()
-               .map(|Point([x, y, z])| {
-                   !data.contains(&Point([x - 1, y, z])) as usize +
-                   !data.contains(&Point([x + 1, y, z])) as usize +
-                   !data.contains(&Point([x, y - 1, z])) as usize +
-                   !data.contains(&Point([x, y + 1, z])) as usize +
-                   !data.contains(&Point([x, y, z - 1])) as usize +
-                   !data.contains(&Point([x, y, z + 1])) as usize
-               })
-               .sum();
+/// Largest number of cells in the grid holding the droplet
+const MAX_CELLS: usize = 1 << 24;
 
-    Ok(sides)
-}
+/// Place the cubes in a grid just big enough to hold all of them
+fn droplet(input: &Input) -> Result<Grid3<bool>> {
+    let min = [0, 1, 2].map(|i| input.iter().map(|p| p.0[i]).min().unwrap_or(0));
+    let max = [0, 1, 2].map(|i| input.iter().map(|p| p.0[i]).max().unwrap_or(-1));
+    let size = if input.is_empty() {
+        [0; 3]
+    } else {
+        [0, 1, 2].map(|i| max[i].abs_diff(min[i]).saturating_add(1))
+    };
+    let cells = size.iter().try_fold(1usize, |n, s| n.checked_mul(*s));
+    anyhow::ensure!(cells.is_some_and(|n| n <= MAX_CELLS),
+                    "Droplet is too large for a dense grid ({}x{}x{})", size[0], size[1], size[2]);
 
-fn solve2(input: &Input) -> Result<usize> {
-    let bound = input.iter().cloned().flat_map(|p| p.0).max().unwrap() + 1;
+    let mut grid = Grid3::filled(size[0], size[1], size[2], false);
+    for Point([x, y, z]) in input {
+        grid.set(((x - min[0]) as usize, (y - min[1]) as usize, (z - min[2]) as usize), true);
+    }
+    Ok(grid)
+}
 
-    // volume in the cube where all coords are between -1 and bound (inclusive)
-    //
-    // simple flood-fill
-    let data = input.iter().cloned().collect::<std::collections::HashSet<_>>();
-    let mut points = vec![[-1, -1, -1]]; // stack of points outside the boundary
-    let mut air = std::collections::HashSet::new();
+fn solve1(input: &Input) -> Result<usize> {
+    Ok(droplet(input)?.surface_area(|c| *c))
+}
 
-    let range = -1..=bound;
-    while let Some(p@[x,y,z]) = points.pop() {
-        air.insert(p);
+fn solve2(input: &Input) -> Result<usize> {
+    Ok(droplet(input)?.exterior_surface_area(|c| *c))
+}
 
-        for delta in [-1isize, 1isize] {
-            if !data.contains(&Point([x+delta, y, z])) &&
-                range.contains(&(x+delta)) &&
-                !air.contains(&[x+delta, y, z])
-            {
-                points.push([x+delta, y, z]);
-            }
-            if !data.contains(&Point([x, y+delta, z])) &&
-                range.contains(&(y+delta)) &&
-                !air.contains(&[x, y+delta, z])
-            {
-                points.push([x, y+delta, z]);
-            }
-            if !data.contains(&Point([x, y, z+delta])) &&
-                range.contains(&(z+delta)) &&
-                !air.contains(&[x, y, z+delta])
-            {
-                points.push([x, y, z+delta]);
-            }
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    let size = 3 + 2 * scale as i64;
+    let mut seen = HashSet::new();
+    let mut out = String::new();
+    for _ in 0..rng.count(1..=5 + 5 * scale) {
+        let cube = [rng.range(0..=size - 1), rng.range(0..=size - 1), rng.range(0..=size - 1)];
+        if seen.insert(cube) {
+            out += &format!("{},{},{}\n", cube[0], cube[1], cube[2]);
         }
     }
-
-    Ok(input.iter().cloned()
-            .map(|Point([x, y, z])| {
-                air.contains(&[x - 1, y, z]) as usize +
-                air.contains(&[x + 1, y, z]) as usize +
-                air.contains(&[x, y - 1, z]) as usize +
-                air.contains(&[x, y + 1, z]) as usize +
-                air.contains(&[x, y, z - 1]) as usize +
-                air.contains(&[x, y, z + 1]) as usize
-            }).sum())
+    out
 }
 
-problem!(crate::util::load_lines => Vec<Point> => (solve1, solve2));
+problem!(crate::util::load_lines => Vec<Point> => (solve1 -> usize, solve2 -> usize),
+         generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+2,2,2
+1,2,2
+3,2,2
+2,1,2
+2,3,2
+2,2,1
+2,2,3
+2,2,4
+2,2,6
+1,2,5
+3,2,5
+2,1,5
+2,3,5
+";
+
+examples! {
+    example: EXAMPLE => part1 = 64, part2 = 58;
+}
@@ -1,6 +1,8 @@
 use anyhow::Result;
 use rayon::prelude::*;
 
+use crate::gen::Rng;
+
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
     const PAT: &str =
         r#"^Blueprint (\d+): Each ore robot costs (\d+) ore. Each clay robot costs (\d+) ore. Each obsidian robot costs (\d+) ore and (\d+) clay. Each geode robot costs (\d+) ore and (\d+) obsidian.$"#;
@@ -15,7 +17,7 @@ fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
     }))
 }
 
-struct Blueprint {
+pub struct Blueprint {
     /// Blueprint number
     index: usize,
 
@@ -212,14 +214,100 @@ fn max_geodes(bp: &Blueprint, t_max: usize) -> usize {
     f(&costs, &mut store, State { key: (t_max as u64 * State::TIMESTEP) | State::ORE_BOT })
 }
 
-fn solve1(input: &Input) -> Result<usize> {
-    Ok(input.par_iter().map(|bp| max_geodes(bp, 24) * bp.index).sum())
+/// Compute the most geodes which can be opened, by trying every order of robot builds
+///
+/// Each build waits until the robot is affordable. Unlike [`max_geodes`], the only builds ruled out
+/// are of robots gathering more of a resource each minute than could ever be spent in one.
+fn max_geodes_brute(bp: &Blueprint, t_max: usize) -> usize {
+    /// Costs of each robot in ore, clay and obsidian, with the geode robot last
+    type Costs = [[usize; 3]; 4];
+
+    fn search(costs: &Costs, bots: [usize; 3], stock: [usize; 3], t: usize) -> usize {
+        let mut best = 0;
+        for (kind, cost) in costs.iter().enumerate() {
+            if kind < 3 && costs.iter().all(|c| bots[kind] >= c[kind]) {
+                continue;
+            }
+            let Some(wait) = (0..t).find(|w| (0..3).all(|r| stock[r] + bots[r] * w >= cost[r])) else {
+                continue;
+            };
+
+            let left = t - wait - 1;
+            let stock = [0, 1, 2].map(|r| stock[r] + bots[r] * (wait + 1) - cost[r]);
+            if kind == 3 {
+                best = best.max(left + search(costs, bots, stock, left));
+            } else {
+                let mut bots = bots;
+                bots[kind] += 1;
+                best = best.max(search(costs, bots, stock, left));
+            }
+        }
+        best
+    }
+
+    let costs = [
+        [bp.b_cost as usize, 0, 0],
+        [bp.c_cost as usize, 0, 0],
+        [bp.o_cost.0 as usize, bp.o_cost.1 as usize, 0],
+        [bp.g_cost.0 as usize, 0, bp.g_cost.1 as usize],
+    ];
+    search(&costs, [1, 0, 0], [0, 0, 0], t_max)
+}
+
+fn solve1(input: &Input, params: &Params) -> Result<usize> {
+    Ok(input.par_iter().map(|bp| max_geodes(bp, params.minutes1) * bp.index).sum())
 }
 
-fn solve2(input: &Input) -> Result<usize> {
-    Ok(input[..3].par_iter()
-                 .map(|bp| max_geodes(bp, 32))
-                 .product())
+fn solve2(input: &Input, params: &Params) -> Result<usize> {
+    // the example only has two blueprints
+    Ok(input[..input.len().min(3)].par_iter()
+                                  .map(|bp| max_geodes(bp, params.minutes2))
+                                  .product())
 }
 
-problem!(load_input => Vec<Blueprint> => (solve1, solve2));
+fn solve1_brute(input: &Input, params: &Params) -> Result<usize> {
+    Ok(input.par_iter().map(|bp| max_geodes_brute(bp, params.minutes1) * bp.index).sum())
+}
+
+fn solve2_brute(input: &Input, params: &Params) -> Result<usize> {
+    Ok(input[..input.len().min(3)].par_iter()
+                                  .map(|bp| max_geodes_brute(bp, params.minutes2))
+                                  .product())
+}
+
+/// Generate blueprints with costs like the real ones
+///
+/// The first few blueprints are cheap enough to open some geodes even when only a few minutes are
+/// allowed, since part 2 multiplies their results together.
+fn generate(rng: &mut Rng, scale: usize, _params: &Params) -> String {
+    (1..=rng.count(1..=scale + 1))
+        .map(|index| {
+            let (ore, clay, obs) = if index <= 3 { (1..=2, 1..=4, 1..=4) } else { (2..=4, 5..=20, 7..=20) };
+            format!("Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+                     Each obsidian robot costs {} ore and {} clay. \
+                     Each geode robot costs {} ore and {} obsidian.\n",
+                    index, rng.range(ore.clone()), rng.range(ore.clone()),
+                    rng.range(ore.clone()), rng.range(clay), rng.range(ore), rng.range(obs))
+        })
+        .collect()
+}
+
+problem!(load_input => Vec<Blueprint> => (solve1 -> usize, solve2 -> usize), params {
+    /// Time limit in part 1
+    minutes1: usize = 24,
+
+    /// Time limit in part 2
+    minutes2: usize = 32,
+}, variants { part1: brute = solve1_brute, part2: brute = solve2_brute }, generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
+Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.
+";
+
+examples! {
+    example: EXAMPLE => part1 = 33,
+                        #[ignore = "takes about a minute, even in release builds"]
+                        part2 = 56 * 62;
+}
@@ -2,6 +2,8 @@ use anyhow::Result;
 
 use std::ptr;
 
+use crate::gen::Rng;
+
 struct ShiftNode<T> {
     value: T,
 
@@ -133,23 +135,80 @@ fn mix_sequence(seq: &[i64], k: usize) -> Vec<i64> {
     seq.into_vec(zero_idx)
 }
 
-fn solve1(input: &Input) -> Result<i64> {
-    let res = mix_sequence(input, 1);
-    Ok(res[1000 % res.len()] +
+/// Mix the same way as [`mix_sequence`], by moving elements around in a plain vector
+fn mix_naive(seq: &[i64], k: usize) -> Vec<i64> {
+    let n = seq.len();
+
+    // original indices, in their current order
+    let mut order = (0..n).collect::<Vec<_>>();
+    for _ in 0..k {
+        for (i, x) in seq.iter().enumerate() {
+            let pos = order.iter().position(|j| *j == i).unwrap();
+            order.remove(pos);
+            order.insert((pos as i64 + x).rem_euclid(n as i64 - 1) as usize, i);
+        }
+    }
+
+    let zero = order.iter().position(|j| seq[*j] == 0).unwrap();
+    (0..n).map(|p| seq[order[(zero + p) % n]]).collect()
+}
+
+/// Sum the grove coordinates from a mixed sequence starting at zero
+fn coordinates(res: &[i64]) -> i64 {
+    res[1000 % res.len()] +
         res[2000 % res.len()] +
-        res[3000 % res.len()])
+        res[3000 % res.len()]
+}
+
+fn decrypt(input: &Input) -> Vec<i64> {
+    input.iter().map(|x| x * 811589153).collect()
+}
+
+fn solve1(input: &Input) -> Result<i64> {
+    Ok(coordinates(&mix_sequence(input, 1)))
 }
 
 fn solve2(input: &Input) -> Result<i64> {
-    let mut data = input.to_owned();
-    for x in &mut data {
-        *x *= 811589153;
-    }
+    Ok(coordinates(&mix_sequence(&decrypt(input), 10)))
+}
 
-    let res = mix_sequence(&data, 10);
-    Ok(res[1000 % res.len()] +
-        res[2000 % res.len()] +
-        res[3000 % res.len()])
+fn solve1_naive(input: &Input) -> Result<i64> {
+    Ok(coordinates(&mix_naive(input, 1)))
 }
 
-problem!(crate::util::load_lines => Vec<i64> => (solve1, solve2));
+fn solve2_naive(input: &Input) -> Result<i64> {
+    Ok(coordinates(&mix_naive(&decrypt(input), 10)))
+}
+
+/// Generate a sequence with a single zero
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    let mut seq = Vec::new();
+    while seq.len() < 2 {
+        seq = (0..rng.count(3..=5 + 10 * scale))
+             .map(|_| rng.range(-1000..=1000))
+             .filter(|x| *x != 0)
+             .collect::<Vec<_>>();
+    }
+    let zero = rng.below(seq.len());
+    seq.insert(zero, 0);
+    seq.iter().map(|x| format!("{}\n", x)).collect()
+}
+
+problem!(crate::util::load_lines => Vec<i64> => (solve1 -> i64, solve2 -> i64),
+         variants { part1: naive = solve1_naive, part2: naive = solve2_naive },
+         generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+1
+2
+-3
+3
+-2
+0
+4
+";
+
+examples! {
+    example: EXAMPLE => part1 = 3, part2 = 1623178306;
+}
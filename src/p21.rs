@@ -1,18 +1,21 @@
 use anyhow::Result;
 
-use std::rc::Rc;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::gen::Rng;
 
 #[derive(Clone, Debug)]
-enum Expr {
+pub enum Expr {
     /// Variable node
     V(i64),
 
     C(i64),
-    Add(Rc<Self>, Rc<Self>),
-    Sub(Rc<Self>, Rc<Self>),
-    Mul(Rc<Self>, Rc<Self>),
-    Div(Rc<Self>, Rc<Self>),
-    Equal(Rc<Self>, Rc<Self>),
+    Add(Arc<Self>, Arc<Self>),
+    Sub(Arc<Self>, Arc<Self>),
+    Mul(Arc<Self>, Arc<Self>),
+    Div(Arc<Self>, Arc<Self>),
+    Equal(Arc<Self>, Arc<Self>),
 }
 
 impl Expr {
@@ -20,11 +23,11 @@ impl Expr {
         match self {
             Self::C(_) => None,
             Self::V(_) => None,
-            Self::Add(l,r) => Some(Self::Equal(Rc::clone(l), Rc::clone(r))),
-            Self::Sub(l,r) => Some(Self::Equal(Rc::clone(l), Rc::clone(r))),
-            Self::Mul(l,r) => Some(Self::Equal(Rc::clone(l), Rc::clone(r))),
-            Self::Div(l,r) => Some(Self::Equal(Rc::clone(l), Rc::clone(r))),
-            Self::Equal(l,r) => Some(Self::Equal(Rc::clone(l), Rc::clone(r))),
+            Self::Add(l,r) => Some(Self::Equal(Arc::clone(l), Arc::clone(r))),
+            Self::Sub(l,r) => Some(Self::Equal(Arc::clone(l), Arc::clone(r))),
+            Self::Mul(l,r) => Some(Self::Equal(Arc::clone(l), Arc::clone(r))),
+            Self::Div(l,r) => Some(Self::Equal(Arc::clone(l), Arc::clone(r))),
+            Self::Equal(l,r) => Some(Self::Equal(Arc::clone(l), Arc::clone(r))),
         }
     }
 
@@ -193,7 +196,7 @@ fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
             Val::V(x) => Expr::V(x),
             Val::Op(lhs, op, rhs) => {
                 let (l, r) = match exprs.get(&lhs).zip(exprs.get(&rhs)) {
-                    Some((l, r)) => (Rc::clone(l), Rc::clone(r)),
+                    Some((l, r)) => (Arc::clone(l), Arc::clone(r)),
                     None => {
                         // one of the args isn't ready yet - process this later
                         pending.push_back((name, Val::Op(lhs, op, rhs)));
@@ -211,13 +214,13 @@ fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
             }
         };
         expr.const_fold();
-        exprs.insert(name, Rc::new(expr));
+        exprs.insert(name, Arc::new(expr));
     }
 
     // resolve root/humn element
     let root = exprs.get("root").ok_or_else(|| anyhow::anyhow!("No root monkey"))?;
 
-    Ok(Rc::clone(root))
+    Ok(Arc::clone(root))
 }
 
 fn solve1(input: &Input) -> Result<i64> {
@@ -230,4 +233,155 @@ fn solve2(input: &Input) -> Result<i64> {
     modified.solve_for(1).ok_or_else(|| anyhow::anyhow!("Unable to solve equation"))
 }
 
-problem!(load_input => Rc<Expr> => (solve1, solve2));
+/// Generate monkeys for a random equation with a known solution
+///
+/// The human's side is built outwards from the answer to part 2, one operation at a time, and every
+/// operation is exact so that it can be solved back. The constants are random trees of monkeys
+/// which are built to produce particular values.
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    /// Cap on the values along the human's side, which keeps everything well clear of overflow
+    const LIMIT: i64 = 1_000_000_000_000;
+
+    struct Monkeys<'r> {
+        rng: &'r mut Rng,
+        names: HashSet<String>,
+        lines: Vec<String>,
+    }
+
+    impl Monkeys<'_> {
+        fn add(&mut self, job: String) -> String {
+            let name = loop {
+                let name = self.rng.word(4);
+                if self.names.insert(name.clone()) {
+                    break name;
+                }
+            };
+            self.lines.push(format!("{}: {}", name, job));
+            name
+        }
+
+        fn constant(&mut self, value: i64, depth: usize) -> String {
+            let rng = &mut *self.rng;
+            let job = match rng.below(if depth == 0 { 1 } else { 5 }) {
+                0 if value >= 0 => value.to_string(),
+                1 => {
+                    let a = rng.range(-100..=100);
+                    format!("{} + {}", self.constant(a, depth - 1), self.constant(value - a, depth - 1))
+                }
+                2 if value != 0 && (2..=9).any(|d| value % d == 0) => {
+                    let d = loop {
+                        let d = rng.range(2..=9);
+                        if value % d == 0 {
+                            break d;
+                        }
+                    };
+                    format!("{} * {}", self.constant(value / d, depth - 1), self.constant(d, depth - 1))
+                }
+                3 => {
+                    let d = rng.range(1..=9);
+                    format!("{} / {}", self.constant(value * d, depth - 1), self.constant(d, depth - 1))
+                }
+                _ => {
+                    // leaves can't be negative
+                    let b = rng.range(0..=100) + (-value).max(0);
+                    format!("{} - {}", self.constant(value + b, depth.saturating_sub(1)),
+                                       self.constant(b, depth.saturating_sub(1)))
+                }
+            };
+            self.add(job)
+        }
+    }
+
+    loop {
+        let (answer, shouted) = (rng.range(1..=10_000), rng.range(0..=10_000));
+        let ops = rng.count(1..=2 + 2 * scale);
+        let mut monkeys = Monkeys {
+            rng: &mut *rng,
+            names: ["root", "humn"].iter().map(|s| s.to_string()).collect(),
+            lines: vec![format!("humn: {}", shouted)],
+        };
+
+        // track the value at the top of the human's side for both parts
+        let (mut value, mut value1, mut node) = (answer, shouted, "humn".to_owned());
+        for _ in 0..ops {
+            let depth = monkeys.rng.count(0..=2);
+            let swap = monkeys.rng.chance(0.5);
+            let job = match monkeys.rng.below(4) {
+                0 if (2..=9).any(|d| value % d == 0) => {
+                    let d = loop {
+                        let d = monkeys.rng.range(2..=9);
+                        if value % d == 0 {
+                            break d;
+                        }
+                    };
+                    (value, value1) = (value / d, value1 / d);
+                    format!("{} / {}", node, monkeys.constant(d, depth))
+                }
+                1 => {
+                    let c = monkeys.rng.range(2..=9);
+                    (value, value1) = (value * c, value1 * c);
+                    let c = monkeys.constant(c, depth);
+                    if swap { format!("{} * {}", c, node) } else { format!("{} * {}", node, c) }
+                }
+                2 => {
+                    let c = monkeys.rng.range(0..=1000);
+                    if swap {
+                        (value, value1) = (c - value, c - value1);
+                        format!("{} - {}", monkeys.constant(c, depth), node)
+                    } else {
+                        (value, value1) = (value - c, value1 - c);
+                        format!("{} - {}", node, monkeys.constant(c, depth))
+                    }
+                }
+                _ => {
+                    let c = monkeys.rng.range(0..=1000);
+                    (value, value1) = (value + c, value1 + c);
+                    let c = monkeys.constant(c, depth);
+                    if swap { format!("{} + {}", c, node) } else { format!("{} + {}", node, c) }
+                }
+            };
+            node = monkeys.add(job);
+
+            if value.abs() > LIMIT || value1.abs() > LIMIT {
+                break;
+            }
+        }
+
+        if value.abs() > LIMIT || value1.abs() > LIMIT {
+            continue;
+        }
+
+        let depth = monkeys.rng.count(0..=2);
+        let other = monkeys.constant(value, depth);
+        monkeys.lines.push(format!("root: {} + {}", node, other));
+
+        let mut lines = monkeys.lines;
+        rng.shuffle(&mut lines);
+        return lines.iter().map(|l| format!("{}\n", l)).collect();
+    }
+}
+
+problem!(load_input => Arc<Expr> => (solve1 -> i64, solve2 -> i64), generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+root: pppw + sjmn
+dbpl: 5
+cczh: sllz + lgvd
+zczc: 2
+ptdq: humn - dvpt
+dvpt: 3
+lfqf: 4
+humn: 5
+ljgn: 2
+sjmn: drzm * dbpl
+sllz: 4
+pppw: cczh / lfqf
+lgvd: ljgn * ptdq
+drzm: hmdt - zczc
+hmdt: 32
+";
+
+examples! {
+    example: EXAMPLE => part1 = 152, part2 = 301;
+}
@@ -1,6 +1,10 @@
 use anyhow::Result;
 
-use crate::grid::{Grid, GridPoint};
+use std::collections::HashMap;
+
+use crate::gen::Rng;
+use crate::grid::{Dir, Grid, GridPoint, Stitched, Topology, WrapVoid};
+use crate::parse::{self, Cursor};
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 enum Cell {
@@ -14,7 +18,7 @@ impl TryFrom<char> for Cell {
 
     fn try_from(value: char) -> Result<Self> {
         match value {
-            '_' => Ok(Self::Unset),
+            ' ' => Ok(Self::Unset),
             '.' => Ok(Self::Space),
             '#' => Ok(Self::Wall),
             _   => Err(anyhow::anyhow!("Invalid cell value"))
@@ -28,44 +32,13 @@ impl Cell {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-enum Dir { Left, Right, Up, Down }
-
-impl Dir {
-    fn turn_left(self) -> Self {
-        match self {
-            Self::Left  => Self::Down,
-            Self::Right => Self::Up,
-            Self::Up    => Self::Left,
-            Self::Down  => Self::Right,
-        }
-    }
-
-    fn turn_right(self) -> Self {
-        match self {
-            Self::Left  => Self::Up,
-            Self::Right => Self::Down,
-            Self::Up    => Self::Right,
-            Self::Down  => Self::Left,
-        }
-    }
-
-    fn as_num(&self) -> usize {
-        match self {
-            Self::Right => 0,
-            Self::Down  => 1,
-            Self::Left  => 2,
-            Self::Up    => 3,
-        }
-    }
-
-    fn flip(&self) -> Self {
-        match self {
-            Self::Up => Self::Down,
-            Self::Down => Self::Up,
-            Self::Left => Self::Right,
-            Self::Right => Self::Left,
-        }
+/// Number for the direction being faced, used in the final password
+fn facing(dir: Dir) -> usize {
+    match dir {
+        Dir::Right => 0,
+        Dir::Down  => 1,
+        Dir::Left  => 2,
+        Dir::Up    => 3,
     }
 }
 
@@ -73,68 +46,56 @@ impl Dir {
 enum Move {
     Left,
     Right,
-    Move(usize),
+    Forward(usize),
 }
 
-struct Problem {
+pub struct Problem {
     map: Grid<Cell>,
 
     directions: Vec<Move>,
 }
 
 fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
+    let data = parse::read_all(input)?;
+    let mut cur = Cursor::new(&data);
+
+    // the map runs up to the first blank line, and its rows can be ragged
     let mut grid_lines = Vec::new();
-    let mut grid_data = String::new();
-    while input.read_line(&mut grid_data)? > 1 {
-        let mut s = std::mem::take(&mut grid_data);
-        s.truncate(s.trim_end().len());
-        grid_lines.push(s);
+    while let Some(line) = cur.next_line() {
+        if line.trim().is_empty() {
+            break;
+        }
+        grid_lines.push(line);
     }
-
-    let grid_width = grid_lines.iter().map(|s| s.len()).max().unwrap();
-    for s in grid_lines.iter_mut() {
-        for _ in s.len()..grid_width {
-            s.push(' ');
+    anyhow::ensure!(!grid_lines.is_empty(), "No map in input");
+
+    let rows = grid_lines.iter().map(|l| l.rest().trim_end()).collect::<Vec<_>>();
+    let grid_width = rows.iter().map(|r| r.chars().count()).max().unwrap();
+    let mut cells = Vec::with_capacity(grid_width * rows.len());
+    for (line, row) in grid_lines.iter().zip(&rows) {
+        for (i, c) in row.char_indices() {
+            let cell = Cell::try_from(c).map_err(|e| line.locate(&row[i..i + c.len_utf8()], e))?;
+            cells.push(cell);
         }
-        *s = s.replace(' ', "_");
+        cells.extend(std::iter::repeat_n(Cell::Unset, grid_width - row.chars().count()));
     }
+    let map = Grid::from_data(cells, grid_width);
 
-    let grid_data = grid_lines.join("\n");
-
-    let mut dirs_line = String::new();
-    anyhow::ensure!(input.read_line(&mut dirs_line)? > 1, "No directions line");
-
-    let mut grid_cursor = std::io::Cursor::new(&grid_data);
-    let map = crate::util::load_grid(&mut grid_cursor)?;
+    let mut dirs_line = cur.next_line()
+                       .map(Cursor::trim)
+                       .filter(|l| !l.is_empty())
+                       .ok_or_else(|| anyhow::anyhow!("No directions line"))?;
 
     let mut directions = Vec::new();
-    let mut accum = String::new();
-    for ch in dirs_line.chars() {
-        match ch {
-            '0'..='9' => {
-                accum.push(ch);
+    while let Some(ch) = dirs_line.peek() {
+        directions.push(match ch {
+            '0'..='9' => Move::Forward(dirs_line.number()?),
+            'L' | 'R' => {
+                dirs_line.next_char();
+                if ch == 'L' { Move::Left } else { Move::Right }
             }
-            'L' => {
-                if !accum.is_empty() {
-                    directions.push(Move::Move(accum.parse()?));
-                    accum.clear();
-                }
-                directions.push(Move::Left);
-            }
-            'R' => {
-                if !accum.is_empty() {
-                    directions.push(Move::Move(accum.parse()?));
-                    accum.clear();
-                }
-                directions.push(Move::Right);
-            }
-            '\n' => {}
-            c => anyhow::bail!("Invalid path character: {}", c)
-        }
-    }
-    if !accum.is_empty() {
-        directions.push(Move::Move(accum.parse()?));
-        accum.clear();
+            c => return Err(dirs_line.error(format!("Invalid path character: {}", c))),
+        });
     }
 
     Ok(Problem { map, directions })
@@ -159,40 +120,7 @@ impl<'g> Actor<'g> {
         }
     }
 
-    /// Get the cell in front of the actor
-    fn forward<W: Fn(&'g Problem, GridPoint<'g, Cell>, Dir) -> (GridPoint<'g, Cell>, Dir)>(
-        &self,
-        map: &'g Problem,
-        wrap: W,
-    ) -> (GridPoint<'g, Cell>, Dir) {
-        let c = match self.dir {
-            Dir::Left => {
-                self.pos.left()
-            },
-            Dir::Right => {
-                self.pos.right()
-            },
-            Dir::Up => {
-                self.pos.up()
-            },
-            Dir::Down => {
-                self.pos.down()
-            },
-        };
-
-        if c.map(|cell| *cell == Cell::Unset).unwrap_or(true) {
-            (wrap)(map, self.pos, self.dir)
-        } else {
-            (c.unwrap(), self.dir)
-        }
-    }
-
-    fn execute<W: Fn(&'g Problem, GridPoint<'g, Cell>, Dir) -> (GridPoint<'g, Cell>, Dir)>(
-        &mut self,
-        map: &'g Problem,
-        insn: &Move,
-        wrap: W,
-    ) {
+    fn execute<Top: Topology<Cell>>(&mut self, insn: &Move, topology: &Top) {
         match insn {
             Move::Left => {
                 self.dir = self.dir.turn_left();
@@ -200,9 +128,9 @@ impl<'g> Actor<'g> {
             Move::Right => {
                 self.dir = self.dir.turn_right();
             }
-            Move::Move(n) => {
+            Move::Forward(n) => {
                 for _ in 0..*n {
-                    let (new_pos, new_dir) = self.forward(map, &wrap);
+                    let Some((new_pos, new_dir)) = self.pos.step(topology, self.dir) else { break };
                     if *new_pos == Cell::Wall {
                         break;
                     }
@@ -216,7 +144,7 @@ impl<'g> Actor<'g> {
     /// Compute the code based on the actor's current state
     fn code(&self) -> usize {
         let (col, row) = self.pos.coords();
-        let dir = self.dir.as_num();
+        let dir = facing(self.dir);
         let col = col + 1; // offset by one due to zero vs one-based indexing
         let row = row + 1;
         1000*row + 4*col + dir
@@ -224,33 +152,10 @@ impl<'g> Actor<'g> {
 }
 
 fn solve1(input: &Input) -> Result<usize> {
-    let row_wrap = (0..input.map.height()).into_iter()
-                  .map(|y| {
-                      let first = input.map.row_iter(y).position(|x| *x != Cell::Unset).unwrap();
-                      let last = input.map.row_iter(y).rposition(|x| *x != Cell::Unset).unwrap();
-                      (first, last)
-                  }).collect::<Vec<_>>();
-    let col_wrap = (0..input.map.width()).into_iter()
-                  .map(|x| {
-                      let first = input.map.col_iter(x).position(|x| *x != Cell::Unset).unwrap();
-                      let last = input.map.col_iter(x).rposition(|x| *x != Cell::Unset).unwrap();
-                      (first, last)
-                  }).collect::<Vec<_>>();
-
+    let topology = WrapVoid::new(|c: &Cell| !c.is_set());
     let mut actor = Actor::new(&input.map);
     for m in &input.directions {
-        actor.execute(input, m, |_, pos, dir| {
-            // wrap around
-            let (col, row) = pos.coords();
-            let out = match dir {
-                Dir::Left => input.map.point((row_wrap[row].1, row)),
-                Dir::Right => input.map.point((row_wrap[row].0, row)),
-                Dir::Up => input.map.point((col, col_wrap[col].1)),
-                Dir::Down => input.map.point((col, col_wrap[col].0)),
-            };
-
-            (out, dir)
-        });
+        actor.execute(m, &topology);
     }
 
     Ok(actor.code())
@@ -364,8 +269,8 @@ impl CubeMapping {
                 min_run
             }
 
-            (0..grid.map.height()).into_iter().map(|y| min_run(grid.map.row_iter(y)))
-                .chain((0..grid.map.width()).into_iter().map(|x| min_run(grid.map.col_iter(x))))
+            (0..grid.map.height()).map(|y| min_run(grid.map.row_iter(y)))
+                .chain((0..grid.map.width()).map(|x| min_run(grid.map.col_iter(x))))
                 .min()
                 .unwrap()
         };
@@ -673,18 +578,111 @@ impl CubeMapping {
 
 fn solve2(input: &Input) -> Result<usize> {
     let mapping = CubeMapping::new(input);
+    let topology = Stitched::new(|c: &Cell| !c.is_set(), |pos: GridPoint<'_, Cell>, dir: Dir| {
+        let (out_pos, out_dir) = mapping.map(pos.coords(), dir);
+        let (unmap_pos, unmap_dir) = mapping.map(out_pos, out_dir.flip());
+        assert_eq!(pos.coords(), unmap_pos);
+        assert_eq!(unmap_dir, dir.flip());
+        Some((out_pos, out_dir))
+    });
+
     let mut actor = Actor::new(&input.map);
     for m in &input.directions {
-        actor.execute(input, m, |_, pos, dir| {
-            let (out_pos, out_dir) = mapping.map(pos.coords(), dir);
-            let (unmap_pos, unmap_dir) = mapping.map(out_pos, out_dir.flip());
-            assert_eq!(pos.coords(), unmap_pos);
-            assert_eq!(unmap_dir, dir.flip());
-            (input.map.point(out_pos), out_dir)
-        });
+        actor.execute(m, &topology);
     }
 
     Ok(actor.code())
 }
 
-problem!(load_input => Problem => (solve1, solve2));
+/// Generate a random net of a cube, with some walls and a path to follow
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    // grow random hexominoes until one folds into a cube, which we check by rolling a cube over
+    // it and seeing that each square touches a different face
+    let net = loop {
+        let mut squares = vec![(0isize, 0isize)];
+        while squares.len() < 6 {
+            let (x, y) = *rng.pick(&squares);
+            let next = *rng.pick(&[(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]);
+            if !squares.contains(&next) {
+                squares.push(next);
+            }
+        }
+
+        // faces are numbered so that opposite faces add up to 5, and the state is the faces which
+        // are on the bottom, north and east sides
+        let mut faces = HashMap::from([(squares[0], (0, 1, 2))]);
+        let mut stack = vec![squares[0]];
+        while let Some((x, y)) = stack.pop() {
+            let (b, n, e) = faces[&(x, y)];
+            for (next, rolled) in [((x + 1, y), (e, n, 5 - b)),
+                                   ((x - 1, y), (5 - e, n, b)),
+                                   ((x, y - 1), (n, 5 - b, e)),
+                                   ((x, y + 1), (5 - n, b, e))] {
+                if squares.contains(&next) && !faces.contains_key(&next) {
+                    faces.insert(next, rolled);
+                    stack.push(next);
+                }
+            }
+        }
+
+        let mut bottoms = faces.values().map(|f| f.0).collect::<Vec<_>>();
+        bottoms.sort_unstable();
+        bottoms.dedup();
+        if bottoms.len() == 6 {
+            break squares;
+        }
+    };
+
+    let (x_min, y_min) = (net.iter().map(|s| s.0).min().unwrap(), net.iter().map(|s| s.1).min().unwrap());
+    let width = (net.iter().map(|s| s.0).max().unwrap() - x_min + 1) as usize;
+    let height = (net.iter().map(|s| s.1).max().unwrap() - y_min + 1) as usize;
+    let side = rng.count(3..=3 + 2 * scale);
+
+    let mut rows = vec![vec![' '; width * side]; height * side];
+    for (x, y) in &net {
+        let (x, y) = ((x - x_min) as usize * side, (y - y_min) as usize * side);
+        for row in &mut rows[y..y + side] {
+            for cell in &mut row[x..x + side] {
+                *cell = if rng.chance(0.15) { '#' } else { '.' };
+            }
+        }
+    }
+
+    // the start is the first open cell on the top row
+    let first = rows[0].iter().position(|c| *c != ' ').unwrap();
+    rows[0][first] = '.';
+
+    let mut out = rows.iter()
+                      .map(|row| row.iter().collect::<String>().trim_end().to_owned() + "\n")
+                      .collect::<String>();
+    out.push('\n');
+    for _ in 0..rng.count(0..=5 * scale) {
+        out += &format!("{}{}", rng.count(1..=3 * side), rng.pick(&['L', 'R']));
+    }
+    out += &format!("{}\n", rng.count(1..=3 * side));
+    out
+}
+
+problem!(load_input => Problem => (solve1 -> usize, solve2 -> usize), generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5
+";
+
+examples! {
+    example: EXAMPLE => part1 = 6032, part2 = 5031;
+}
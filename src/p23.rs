@@ -1,9 +1,12 @@
 use anyhow::Result;
 
-use crate::grid::Grid;
+use crate::gen::Rng;
+use crate::grid::{Grid, InfiniteGrid};
+use crate::record::{record_with, Recorder};
+use crate::solution::Inspect;
 
 #[derive(Copy, Clone, PartialEq, Eq)]
-enum Cell {
+pub enum Cell {
     Empty,
     Elf,
 }
@@ -35,18 +38,14 @@ impl Action {
         Action::Up, Action::Down, Action::Left, Action::Right
     ];
 
-    fn feasible(
-        &self,
-        (x, y): (isize, isize),
-        presence: &fnv::FnvHashSet<(isize, isize)>
-    ) -> bool {
+    fn feasible(&self, (x, y): (isize, isize), presence: &InfiniteGrid<bool>) -> bool {
         let (a, b, c) = match self {
             Self::Up    => ((x-1, y-1), (x, y-1), (x+1, y-1)),
             Self::Down  => ((x-1, y+1), (x, y+1), (x+1, y+1)),
             Self::Left  => ((x-1, y+1), (x-1, y), (x-1, y-1)),
             Self::Right => ((x+1, y+1), (x+1, y), (x+1, y-1)),
         };
-        !(presence.contains(&a) || presence.contains(&b) || presence.contains(&c))
+        !(*presence.get(a) || *presence.get(b) || *presence.get(c))
     }
 
     fn target(&self, (x, y): (isize, isize)) -> (isize, isize) {
@@ -59,22 +58,15 @@ impl Action {
     }
 }
 
-fn neighbors((x, y): (isize, isize), presence: &fnv::FnvHashSet<(isize, isize)>) -> usize {
-    presence.contains(&(x-1, y-1)) as usize +
-    presence.contains(&(x  , y-1)) as usize +
-    presence.contains(&(x+1, y-1)) as usize +
-    presence.contains(&(x-1, y  )) as usize +
-    presence.contains(&(x+1, y  )) as usize +
-    presence.contains(&(x-1, y+1)) as usize +
-    presence.contains(&(x  , y+1)) as usize +
-    presence.contains(&(x+1, y+1)) as usize
-}
-
 struct Simulation {
     elves: Vec<Coords>,
-    presence: fnv::FnvHashSet<Coords>,
-    proposed: fnv::FnvHashMap<Coords, Action>,
-    collider: fnv::FnvHashMap<Coords, u8>,
+    presence: InfiniteGrid<bool>,
+
+    /// Where each elf wants to move to, if anywhere
+    proposed: Vec<Option<Coords>>,
+
+    /// Number of elves wanting to move to each cell, which is left zeroed between rounds
+    collider: InfiniteGrid<u8>,
     order: usize, // start offset into [NSWE] array
 }
 
@@ -85,51 +77,46 @@ impl Simulation {
                    .map(|p| p.coords())
                    .map(|(x, y)| (x as isize, y as isize))
                    .collect::<Vec<_>>();
-        let presence = fnv::FnvHashSet::default();
-        let proposed = fnv::FnvHashMap::default();
-        let collider = fnv::FnvHashMap::default();
+        let mut presence = InfiniteGrid::new(false);
+        for elf in &elves {
+            presence.set(*elf, true);
+        }
 
-        Self { elves, presence, proposed, collider, order: 0 }
+        Self { elves, presence, proposed: Vec::new(), collider: InfiniteGrid::new(0), order: 0 }
     }
 
     fn tick(&mut self) {
         // first half
-        self.presence.clear();
-        self.presence.extend(self.elves.iter().cloned());
-
         self.proposed.clear();
-        self.proposed.extend(self.elves.iter().cloned()
-                            .filter(|elf| neighbors(*elf, &self.presence) != 0)
-                            .filter_map(|elf| {
-                                // choose direction
-                                for i in 0..4 {
-                                    let offs = (self.order + i) % 4;
-                                    let dir = Action::DIRECTIONS[offs];
-                                    if dir.feasible(elf, &self.presence) {
-                                        return Some((elf, dir));
-                                    }
-                                }
-
-                                None
-                            }));
+        self.proposed.extend(self.elves.iter().map(|elf| {
+            if !self.presence.neighbors(*elf).any(|(_, e)| *e) {
+                return None;
+            }
+
+            // choose direction
+            (0..4).map(|i| Action::DIRECTIONS[(self.order + i) % 4])
+                  .find(|dir| dir.feasible(*elf, &self.presence))
+                  .map(|dir| dir.target(*elf))
+        }));
 
         // update collision logic
-        self.collider.clear();
-        for e in &self.elves {
-            if let Some(dir) = self.proposed.get(e) {
-                *self.collider.entry(dir.target(*e)).or_insert(0) += 1;
-            }
+        for tgt in self.proposed.iter().flatten() {
+            *self.collider.get_mut(*tgt) += 1;
         }
 
         // second half
-        for e in self.elves.iter_mut() {
-            if let Some(dir) = self.proposed.get(e) {
-                let tgt = dir.target(*e);
-                if self.collider.get(&tgt) == Some(&1) {
-                    *e = tgt;
+        for (e, tgt) in self.elves.iter_mut().zip(&self.proposed) {
+            if let Some(tgt) = tgt {
+                if *self.collider.get(*tgt) == 1 {
+                    self.presence.set(*e, false);
+                    self.presence.set(*tgt, true);
+                    *e = *tgt;
                 }
             }
         }
+        for tgt in self.proposed.iter().flatten() {
+            self.collider.set(*tgt, 0);
+        }
 
         // direction updates
         self.order = (self.order + 1) % 4;
@@ -144,6 +131,19 @@ impl Simulation {
         ((max_x - min_x + 1) as usize,
          (max_y - min_y + 1) as usize)
     }
+
+    /// Draw the elves, returning the picture and the position of its top-left corner
+    fn draw(&self) -> ((isize, isize), Grid<char>) {
+        let min_x = self.elves.iter().map(|e| e.0).min().unwrap_or(0);
+        let min_y = self.elves.iter().map(|e| e.1).min().unwrap_or(0);
+        let (width, height) = self.bounds();
+
+        let mut grid = Grid::filled(width, height, '.');
+        for (x, y) in &self.elves {
+            grid.set(((x - min_x) as usize, (y - min_y) as usize), '#');
+        }
+        ((min_x, min_y), grid)
+    }
 }
 
 fn solve1(input: &Input) -> Result<usize> {
@@ -172,4 +172,84 @@ fn solve2(input: &Input) -> Result<usize> {
     Ok(round)
 }
 
-problem!(crate::util::load_grid => Grid<Cell> => (solve1, solve2));
+/// Stepping through the simulation a round at a time
+///
+/// Both parts run the same simulation, only looking at it at different times.
+struct Inspector {
+    sim: Simulation,
+    round: usize,
+}
+
+impl Inspect for Inspector {
+    fn step(&mut self) -> Result<bool> {
+        let last_elves = self.sim.elves.clone();
+        self.sim.tick();
+        if self.sim.elves == last_elves {
+            return Ok(false);
+        }
+        self.round += 1;
+        Ok(true)
+    }
+
+    fn show(&self) -> String {
+        let (_, grid) = self.sim.draw();
+        let empty = grid.cells().filter(|c| **c == '.').count();
+        format!("After round {} ({} empty tiles):\n{}",
+                self.round, empty, grid.render_with(|c| *c).trim_end())
+    }
+}
+
+fn inspect(input: &Input, _part: usize) -> Inspector {
+    Inspector { sim: Simulation::new(input), round: 0 }
+}
+
+/// Record the elves spreading out, for 10 rounds in part 1 or until they stop in part 2
+fn record(input: &Input, part: usize, rec: &mut dyn Recorder) -> Result<()> {
+    let mut rec = Some(rec);
+    let mut sim = Simulation::new(input);
+    record_with(&mut rec, || sim.draw());
+    for round in 1.. {
+        let last_elves = sim.elves.clone();
+        sim.tick();
+        if sim.elves == last_elves || (part == 1 && round > 10) {
+            break;
+        }
+        record_with(&mut rec, || sim.draw());
+    }
+    Ok(())
+}
+
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    loop {
+        let (width, height) = (rng.count(3..=3 + 3 * scale), rng.count(3..=3 + 3 * scale));
+        let density = rng.range(20..=60) as f64 / 100.0;
+        let out = (0..height)
+                 .map(|_| {
+                     let row = (0..width).map(|_| if rng.chance(density) { '#' } else { '.' });
+                     row.chain(std::iter::once('\n')).collect::<String>()
+                 })
+                 .collect::<String>();
+
+        if out.chars().filter(|c| *c == '#').count() >= 5 {
+            break out;
+        }
+    }
+}
+
+problem!(crate::util::load_grid => Grid<Cell> => (solve1 -> usize, solve2 -> usize),
+         generator generate, inspect inspect, record record);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+....#..
+..###.#
+#...#.#
+.#...##
+#.###..
+##.#.##
+.#..#..
+";
+
+examples! {
+    example: EXAMPLE => part1 = 110, part2 = 20;
+}
@@ -1,61 +1,59 @@
 use anyhow::Result;
 
+use crate::gen::Rng;
+use crate::grid::{BitGrid, Grid};
+use crate::record::{record_with, Recorder};
+
 #[derive(Clone, Debug)]
 /// Map of which blizzards are in which cells
 ///
-/// Blizzards are represented as set bits in a bitmask, and one bitmap is stored per row and
-/// column. Each blizzard direction is stored in its own vector, and the blizzard state of any
-/// given cell on the board can be tested with simple bitwise operations.
+/// Each blizzard direction is stored in its own bit grid, so moving every blizzard is a single
+/// rotation of each grid, and the blizzard state of any given cell can be tested with a few lookups.
 struct Blizzards {
-    // up/down blizzards. high-order bits store the bottom of the board.
-    up: Vec<u128>,
-    down: Vec<u128>,
-    height: u32,
-
-    // left/right blizzards. high-order bits store the right of the board.
-    left: Vec<u128>,
-    right: Vec<u128>,
-    width: u32,
+    up: BitGrid,
+    down: BitGrid,
+    left: BitGrid,
+    right: BitGrid,
+
+    width: usize,
+    height: usize,
 }
 
 impl Blizzards {
     fn step(&mut self) {
-        /// Rotate the `k` lowest-order bits of N leftwards
-        fn rot_left(n: u128, k: u32) -> u128 {
-            let mask = (1 << k) - 1;
-            let b = (n >> (k-1)) & 1;
-            ((n << 1) & mask) | b
-        }
-
-        /// Rotate the `k` lowest-order bits of N rightwards
-        fn rot_right(n: u128, k: u32) -> u128 {
-            let b = n & 1;
-            (n >> 1) | (b << (k-1))
-        }
-
-        for x in self.up.iter_mut() {
-            *x = rot_right(*x, self.height);
-        }
-        for x in self.down.iter_mut() {
-            *x = rot_left(*x, self.height);
-        }
+        self.up.rotate_up(1);
+        self.down.rotate_down(1);
+        self.left.rotate_left(1);
+        self.right.rotate_right(1);
+    }
 
-        for x in self.left.iter_mut() {
-            *x = rot_right(*x, self.width);
-        }
-        for x in self.right.iter_mut() {
-            *x = rot_left(*x, self.width);
-        }
+    fn is_free(&self, pos: (usize, usize)) -> bool {
+        !(self.up.get(pos) || self.down.get(pos) || self.left.get(pos) || self.right.get(pos))
     }
 
-    fn is_free(&self, (x, y): (usize, usize)) -> bool {
-        let ud = self.up[x] | self.down[x];
-        let lr = self.left[y] | self.right[y];
-        ((ud & (1 << y)) | (lr & (1 << x))) == 0
+    /// Draw the valley as in the puzzle, with the expedition's possible positions as `E`
+    fn draw(&self, expedition: impl Iterator<Item=(usize, usize)>) -> Grid<char> {
+        let mut grid = Grid::from_fn(self.width, self.height, |x, y| {
+            let arrows = [
+                (self.up.get((x, y)), '^'),
+                (self.down.get((x, y)), 'v'),
+                (self.left.get((x, y)), '<'),
+                (self.right.get((x, y)), '>'),
+            ];
+            match arrows.iter().filter(|a| a.0).count() {
+                0 => '.',
+                1 => arrows.iter().find(|a| a.0).unwrap().1,
+                n => char::from_digit(n as u32, 10).unwrap(),
+            }
+        });
+        for pos in expedition {
+            grid.set(pos, 'E');
+        }
+        grid
     }
 }
 
-struct Problem {
+pub struct Problem {
     /// Entry position above the first row
     enter_col: usize,
 
@@ -92,50 +90,21 @@ fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
 
     let main_width = cells.width() - 2; // ignore outer walls
     let main_height = cells.height() - 2;
-    anyhow::ensure!(!(main_height > 128 || main_width > 128),
-                    "Grid is too large for this implementation");
     let enter_col = cells.row_iter(0).skip(1).position(|c| matches!(c, Cell::Empty))
                    .ok_or_else(|| anyhow::anyhow!("No entry position"))?;
     let leave_col = cells.row_iter(cells.height()-1).skip(1).position(|c| matches!(c, Cell::Empty))
                    .ok_or_else(|| anyhow::anyhow!("No entry position"))?;
 
-    // build blizzard bitmasks
-    let mut storms = Blizzards {
-        width: main_width as u32,
-        height: main_height as u32,
-        up: Vec::with_capacity(main_width),
-        down: Vec::with_capacity(main_width),
-        left: Vec::with_capacity(main_height),
-        right: Vec::with_capacity(main_height),
+    // build blizzard bitmaps
+    let blizzards = |dir| BitGrid::from_fn(main_width, main_height, |x, y| *cells.get((x + 1, y + 1)) == dir);
+    let storms = Blizzards {
+        up: blizzards(Cell::Up),
+        down: blizzards(Cell::Down),
+        left: blizzards(Cell::Left),
+        right: blizzards(Cell::Right),
+        width: main_width,
+        height: main_height,
     };
-    for col in 0..main_width {
-        let mut up = 0;
-        let mut down = 0;
-        for (i, cell) in cells.col_iter(1+col).skip(1).enumerate() {
-            if *cell == Cell::Up {
-                up |= 1 << i;
-            }
-            if *cell == Cell::Down {
-                down |= 1 << i;
-            }
-        }
-        storms.up.push(up);
-        storms.down.push(down);
-    }
-    for row in 0..main_height {
-        let mut left = 0;
-        let mut right = 0;
-        for (i, cell) in cells.row_iter(1+row).skip(1).enumerate() {
-            if *cell == Cell::Left {
-                left |= 1 << i;
-            }
-            if *cell == Cell::Right {
-                right |= 1 << i;
-            }
-        }
-        storms.left.push(left);
-        storms.right.push(right);
-    }
 
     for p in cells.points() {
         let (x,y) = p.coords();
@@ -153,10 +122,16 @@ fn load_input(input: &mut dyn std::io::BufRead) -> Result<Input> {
 
 /// Find the length of the shortest sequence of moves, if any, which will take an actor from the
 /// entry to the exit safely.
+///
+/// A frame is drawn for each minute, showing every position the expedition could be in.
+///
+/// This doesn't use [`crate::grid::search`], since the open cells change every minute and waiting
+/// in place is a move. Searching a minute at a time keeps only the current blizzards around.
 fn shortest(
     mut storms: Blizzards,
     start: (usize, usize),
     end: (usize, usize),
+    rec: &mut Option<&mut dyn Recorder>,
 ) -> Option<(usize, Blizzards)> {
     #[derive(Debug)]
     struct State {
@@ -168,11 +143,11 @@ fn shortest(
     
     // advance until there's an open space below the entryway
 
-    let max_w = storms.width as usize - 1;
-    let max_h = storms.height as usize - 1;
+    let max_w = storms.width - 1;
+    let max_h = storms.height - 1;
 
     // queued positions for t+1
-    let mut seen = crate::grid::Grid::filled(storms.width as usize, storms.height as usize, false);
+    let mut seen = BitGrid::new(storms.width, storms.height);
 
     let mut next_storm = storms.clone();
     next_storm.step();
@@ -183,6 +158,7 @@ fn shortest(
             storms.step();
             next_storm.step();
             storm_t += 1;
+            record_with(rec, || ((1, 1), storms.draw(std::iter::empty())));
             continue;
         }
         states.push_back(State { t: storm_t, pos: start });
@@ -194,11 +170,15 @@ fn shortest(
                 storm_t += 1;
 
                 // add state for waiting
-                if storms.is_free(start) && !*seen.get(start) {
+                if storms.is_free(start) && !seen.get(start) {
                     states.push_back(State { pos: start, t: storm_t });
                 }
                 seen.fill(false);
 
+                record_with(rec, || {
+                    let positions = states.iter().map(|s| s.pos).chain(std::iter::once(pos));
+                    ((1, 1), storms.draw(positions.filter(|p| storms.is_free(*p))))
+                });
             }
             if !storms.is_free(pos) {
                 continue;
@@ -208,20 +188,20 @@ fn shortest(
             }
 
             // try to move in every direction
-            if pos.0 > 0 && next_storm.is_free((pos.0 - 1, pos.1)) && !*seen.get((pos.0 - 1, pos.1)) {
+            if pos.0 > 0 && next_storm.is_free((pos.0 - 1, pos.1)) && !seen.get((pos.0 - 1, pos.1)) {
                 states.push_back(State { pos: (pos.0 - 1, pos.1), t: t+1 });
                 seen.set((pos.0 - 1, pos.1), true);
             }
-            if pos.0 < max_w && next_storm.is_free((pos.0 + 1, pos.1)) && !*seen.get((pos.0 + 1, pos.1)) {
+            if pos.0 < max_w && next_storm.is_free((pos.0 + 1, pos.1)) && !seen.get((pos.0 + 1, pos.1)) {
                 states.push_back(State { pos: (pos.0 + 1, pos.1), t: t+1 });
                 seen.set((pos.0 + 1, pos.1), true);
             }
 
-            if pos.1 > 0 && next_storm.is_free((pos.0, pos.1 - 1)) && !*seen.get((pos.0, pos.1 - 1)) {
+            if pos.1 > 0 && next_storm.is_free((pos.0, pos.1 - 1)) && !seen.get((pos.0, pos.1 - 1)) {
                 states.push_back(State { pos: (pos.0, pos.1 - 1), t: t+1 });
                 seen.set((pos.0, pos.1 - 1), true);
             }
-            if pos.1 < max_h && next_storm.is_free((pos.0, pos.1 + 1)) && !*seen.get((pos.0, pos.1 + 1)) {
+            if pos.1 < max_h && next_storm.is_free((pos.0, pos.1 + 1)) && !seen.get((pos.0, pos.1 + 1)) {
                 states.push_back(State { pos: (pos.0, pos.1 + 1), t: t+1 });
                 seen.set((pos.0, pos.1 + 1), true);
             }
@@ -232,26 +212,105 @@ fn shortest(
 
 fn solve1(input: &Input) -> Result<usize> {
     let entry_pos = (input.enter_col, 0);
-    let exit_pos = (input.leave_col, input.storms.height as usize - 1);
-    shortest(input.storms.clone(), entry_pos, exit_pos)
+    let exit_pos = (input.leave_col, input.storms.height - 1);
+    shortest(input.storms.clone(), entry_pos, exit_pos, &mut None)
         .map(|t| t.0)
         .ok_or_else(|| anyhow::anyhow!("Unable to find shortest path"))
 }
 
-fn solve2(input: &Input) -> Result<usize> {
-    // journey there
+/// Go to the exit, back to the entrance, then to the exit again, returning the total time
+fn there_and_back(input: &Input, mut rec: Option<&mut dyn Recorder>) -> Result<usize> {
     let entry_pos = (input.enter_col, 0);
-    let exit_pos = (input.leave_col, input.storms.height as usize - 1);
-    let (t0, mut storms) = shortest(input.storms.clone(), entry_pos, exit_pos)
+    let exit_pos = (input.leave_col, input.storms.height - 1);
+    let (t0, mut storms) = shortest(input.storms.clone(), entry_pos, exit_pos, &mut rec)
                           .ok_or_else(|| anyhow::anyhow!("Unable to solve first leg"))?;
     storms.step();
-    let (t1, mut storms) = shortest(storms, exit_pos, entry_pos)
+    let (t1, mut storms) = shortest(storms, exit_pos, entry_pos, &mut rec)
                           .ok_or_else(|| anyhow::anyhow!("Unable to solve second leg"))?;
     storms.step();
-    let (t2, _) = shortest(storms, entry_pos, exit_pos)
+    let (t2, _) = shortest(storms, entry_pos, exit_pos, &mut rec)
                  .ok_or_else(|| anyhow::anyhow!("Unable to solve third leg"))?;
 
     Ok(t0 + t1 + t2)
 }
 
-problem!(load_input => Problem => (solve1, solve2));
+fn solve2(input: &Input) -> Result<usize> {
+    there_and_back(input, None)
+}
+
+fn record(input: &Input, part: usize, rec: &mut dyn Recorder) -> Result<()> {
+    if part == 1 {
+        let entry_pos = (input.enter_col, 0);
+        let exit_pos = (input.leave_col, input.storms.height - 1);
+        shortest(input.storms.clone(), entry_pos, exit_pos, &mut Some(rec))
+            .ok_or_else(|| anyhow::anyhow!("Unable to find shortest path"))?;
+    } else {
+        there_and_back(input, Some(rec))?;
+    }
+    Ok(())
+}
+
+/// Generate a valley where a path there and back is always possible
+///
+/// Both paths run down the entry column and along the bottom row. Blizzards which would cross
+/// either one around the time it's taken are left out, so the paths come back clear every time
+/// the blizzards repeat, and the expedition can wait at the entrance until then.
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    let width = rng.count(3..=5 + 5 * scale);
+    let height = rng.count(3..=5 + 5 * scale);
+
+    let there = (0..height).map(|y| (0, y)).chain((1..width).map(|x| (x, height - 1)));
+    let back = (0..width).rev().map(|x| (x, height - 1)).chain((0..height - 1).rev().map(|y| (0, y)));
+    let path = there.enumerate().chain(back.enumerate()).collect::<Vec<_>>();
+
+    let density = rng.range(0..=30) as f64 / 100.0;
+    let mut rows = vec![vec!['.'; width]; height];
+    for (y, row) in rows.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            if !rng.chance(density) {
+                continue;
+            }
+
+            let (c, (dx, dy)) = *rng.pick(&[('>', (1, 0)), ('<', (-1, 0)), ('v', (0, 1)), ('^', (0, -1))]);
+            if dy != 0 && (x == 0 || x == width - 1) {
+                continue;
+            }
+
+            let hits = path.iter().any(|(step, pos)| {
+                (step.saturating_sub(2)..=step + 2).any(|t| {
+                    let t = t as isize;
+                    let bx = (x as isize + dx * t).rem_euclid(width as isize) as usize;
+                    let by = (y as isize + dy * t).rem_euclid(height as isize) as usize;
+                    (bx, by) == *pos
+                })
+            });
+            if !hits {
+                *cell = c;
+            }
+        }
+    }
+
+    let mut out = format!("#.{}\n", "#".repeat(width));
+    for row in rows {
+        out += &format!("#{}#\n", row.into_iter().collect::<String>());
+    }
+    out += &format!("{}.#\n", "#".repeat(width));
+    out
+}
+
+problem!(load_input => Problem => (solve1 -> usize, solve2 -> usize),
+         positive { part1, part2 }, generator generate, record record);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#
+";
+
+examples! {
+    example: EXAMPLE => part1 = 18, part2 = 54;
+}
@@ -1,9 +1,11 @@
 use anyhow::Result;
 
+use crate::gen::Rng;
+
 type Digit = i8;
 
 #[derive(Debug, PartialEq, Eq)]
-struct Number {
+pub struct Number {
     /// Digits, in order of increasing significance
     digits: Vec<Digit>,
 }
@@ -105,4 +107,38 @@ fn solve1(input: &Input) -> Result<String> {
     Ok(Number::from_number(s).to_string())
 }
 
-problem!(crate::util::load_lines => Vec<Number> => (solve1));
+fn generate(rng: &mut Rng, scale: usize) -> String {
+    (0..rng.count(1..=5 * scale))
+        .map(|_| {
+            // a leading 1 or 2 keeps every number positive
+            let mut number = rng.pick(&['1', '2']).to_string();
+            for _ in 0..rng.count(0..=12) {
+                number.push(*rng.pick(&['2', '1', '0', '-', '=']));
+            }
+            number + "\n"
+        })
+        .collect()
+}
+
+problem!(crate::util::load_lines => Vec<Number> => (solve1 -> String), generator generate);
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+1=-0-2
+12111
+2=0=
+21
+2=01
+111
+20012
+112
+1=-1=
+1-12
+12
+1=
+122
+";
+
+examples! {
+    example: EXAMPLE => part1 = "2=-1=0";
+}
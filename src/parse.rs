@@ -0,0 +1,343 @@
+use anyhow::Result;
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// An error at a particular place in an input file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line number, counting from 1
+    pub line: usize,
+
+    /// Column where the offending text starts, in characters counting from 1
+    pub column: usize,
+
+    /// Length of the offending text in characters, which is 0 for text that's missing
+    pub len: usize,
+
+    /// The whole line containing the error, without its line ending
+    pub text: String,
+
+    pub message: String,
+}
+
+impl ParseError {
+    /// Build an error for a byte range of an input
+    ///
+    /// Ranges which run over the end of a line are cut off there.
+    fn new(input: &str, start: usize, end: usize, message: String) -> Self {
+        let line_start = input[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[start..].find('\n').map_or(input.len(), |i| start + i);
+        let text = input[line_start..line_end].trim_end_matches('\r');
+        let end = end.min(line_start + text.len()).max(start);
+
+        Self {
+            line: input.as_bytes()[..line_start].iter().filter(|b| **b == b'\n').count() + 1,
+            column: input[line_start..start].chars().count() + 1,
+            len: input[start..end].chars().count(),
+            text: text.to_owned(),
+            message,
+        }
+    }
+
+    /// The offending line with carets under the offending text, as shown by the runner
+    pub fn snippet(&self) -> String {
+        let gutter = self.line.to_string();
+        let pad = " ".repeat(gutter.len());
+        // keep tabs, so that the carets line up with the text above them
+        let indent = self.text.chars()
+                    .take(self.column - 1)
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect::<String>();
+
+        format!("{} |\n{} | {}\n{} | {}{}",
+                pad, gutter, self.text, pad, indent, "^".repeat(self.len.max(1)))
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Position within an input being parsed, which keeps track of where it is for error messages
+///
+/// A cursor covers part of the input, such as a single line or a blank-separated block of lines.
+/// Errors made by a cursor, including those for text which has already been taken from it, point
+/// at their real line and column in the whole input.
+#[derive(Copy, Clone, Debug)]
+pub struct Cursor<'a> {
+    input: &'a str,
+    pos: usize,
+    end: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, pos: 0, end: input.len() }
+    }
+
+    /// The text which hasn't been parsed yet
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.pos..self.end]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.end
+    }
+
+    /// The next character, without taking it
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Take the next character
+    pub fn next_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// Take some text if it's what comes next, returning whether it was
+    pub fn eat(&mut self, s: &str) -> bool {
+        let found = self.rest().starts_with(s);
+        if found {
+            self.pos += s.len();
+        }
+        found
+    }
+
+    /// Take some text which has to come next
+    pub fn expect(&mut self, s: &str) -> Result<()> {
+        if self.eat(s) {
+            return Ok(());
+        }
+
+        // point at as much text as was expected
+        let found = self.rest().char_indices()
+                   .nth(s.chars().count())
+                   .map_or(self.end, |(i, _)| self.pos + i);
+        Err(self.error_span(self.pos, found, format!("Expected '{}'", s)))
+    }
+
+    /// Take characters for as long as they match a predicate
+    pub fn take_while(&mut self, mut pred: impl FnMut(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Take text up to a separator, skipping over the separator, or all of it if there's none
+    pub fn take_until(&mut self, sep: &str) -> &'a str {
+        let rest = self.rest();
+        match rest.find(sep) {
+            Some(len) => {
+                self.pos += len + sep.len();
+                &rest[..len]
+            }
+            None => {
+                self.pos = self.end;
+                rest
+            }
+        }
+    }
+
+    /// Take an integer, which may have a sign
+    pub fn number<T: FromStr>(&mut self) -> Result<T>
+    where T::Err: Display,
+    {
+        let start = self.pos;
+        if !self.eat("-") {
+            self.eat("+");
+        }
+        if self.take_while(|c| c.is_ascii_digit()).is_empty() {
+            let err = self.error("Expected a number");
+            self.pos = start;
+            return Err(err);
+        }
+
+        self.parse_at(&self.input[start..self.pos])
+    }
+
+    /// Parse all of the remaining text
+    pub fn parse<T: FromStr>(mut self) -> Result<T>
+    where T::Err: Display,
+    {
+        let rest = self.rest();
+        self.pos = self.end;
+        self.parse_at(rest)
+    }
+
+    /// Parse some text taken from this cursor, with any error pointing at that text
+    pub fn parse_at<T: FromStr>(&self, s: &str) -> Result<T>
+    where T::Err: Display,
+    {
+        s.parse().map_err(|e| self.error_at(s, format!("Invalid value '{}': {}", s, e)))
+    }
+
+    /// The text taken since an earlier copy of this cursor
+    pub fn since(&self, mark: Cursor<'a>) -> &'a str {
+        &self.input[mark.pos..self.pos]
+    }
+
+    /// A copy of this cursor with nothing left to parse, for errors about missing text
+    pub fn at_end(self) -> Self {
+        Self { pos: self.end, ..self }
+    }
+
+    /// Skip whitespace at both ends of the remaining text
+    pub fn trim(self) -> Self {
+        let rest = self.rest();
+        let start = self.pos + (rest.len() - rest.trim_start().len());
+        Self { pos: start, end: start + rest.trim().len(), ..self }
+    }
+
+    /// Check that there's nothing left to parse
+    pub fn finish(&self) -> Result<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error_at(self.rest(), "Unexpected trailing text"))
+        }
+    }
+
+    /// Take the next line, which is returned without its line ending
+    pub fn next_line(&mut self) -> Option<Cursor<'a>> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let start = self.pos;
+        let len = self.rest().find('\n');
+        self.pos = len.map_or(self.end, |n| start + n + 1);
+        let end = len.map_or(self.end, |n| start + n);
+        let end = if self.input[start..end].ends_with('\r') { end - 1 } else { end };
+        Some(Self { input: self.input, pos: start, end })
+    }
+
+    /// Iterate over the remaining lines
+    pub fn lines(mut self) -> impl Iterator<Item=Cursor<'a>> {
+        std::iter::from_fn(move || self.next_line())
+    }
+
+    /// Take the next block of lines, up to a blank line or the end of the input
+    ///
+    /// Blank lines before the block are skipped, and the blank line after it is taken too.
+    pub fn next_block(&mut self) -> Option<Cursor<'a>> {
+        let mut block = None::<Cursor>;
+        while let Some(line) = self.next_line() {
+            match (&mut block, line.trim().is_empty()) {
+                (None, true) => {}
+                (None, false) => block = Some(line),
+                (Some(_), true) => break,
+                (Some(block), false) => block.end = line.end,
+            }
+        }
+        block
+    }
+
+    /// Line and column of the current position, counting from 1
+    pub fn position(&self) -> (usize, usize) {
+        let err = ParseError::new(self.input, self.pos, self.pos, String::new());
+        (err.line, err.column)
+    }
+
+    /// An error pointing at the next character
+    pub fn error(&self, msg: impl Display) -> anyhow::Error {
+        let next = self.peek().map_or(0, char::len_utf8);
+        self.error_span(self.pos, self.pos + next, msg.to_string())
+    }
+
+    /// An error pointing at some text taken from the input
+    ///
+    /// If the text isn't part of this cursor's input, the error points at the current position.
+    pub fn error_at(&self, s: &str, msg: impl Display) -> anyhow::Error {
+        match self.offset_of(s) {
+            Some(start) => self.error_span(start, start + s.len(), msg.to_string()),
+            None => self.error(msg),
+        }
+    }
+
+    /// Point an error from parsing some text at it, unless the error already has a position
+    pub fn locate(&self, s: &str, err: anyhow::Error) -> anyhow::Error {
+        if err.chain().any(|e| e.is::<ParseError>()) {
+            err
+        } else {
+            self.error_at(s, format!("{:#}", err))
+        }
+    }
+
+    fn offset_of(&self, s: &str) -> Option<usize> {
+        let base = self.input.as_ptr() as usize;
+        let start = (s.as_ptr() as usize).checked_sub(base)?;
+        (start + s.len() <= self.input.len()).then_some(start)
+    }
+
+    fn error_span(&self, start: usize, end: usize, msg: String) -> anyhow::Error {
+        ParseError::new(self.input, start, end, msg).into()
+    }
+}
+
+/// Read the whole of an input, for parsing with a [`Cursor`]
+pub fn read_all(input: &mut dyn std::io::BufRead) -> Result<String> {
+    let mut data = String::new();
+    input.read_to_string(&mut data)?;
+    Ok(data)
+}
+
+/// Find the position of a parse error in an error's chain of causes, if there is one
+pub fn find_error(err: &anyhow::Error) -> Option<&ParseError> {
+    err.chain().find_map(|e| e.downcast_ref::<ParseError>())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn position(err: anyhow::Error) -> (usize, usize, usize) {
+        let err = find_error(&err).expect("No position in error");
+        (err.line, err.column, err.len)
+    }
+
+    #[test]
+    fn positions() {
+        let input = "first line\r\n\n  12, x7\n";
+        let mut cur = Cursor::new(input);
+        let lines = cur.lines().collect::<Vec<_>>();
+        assert_eq!(lines.iter().map(|l| l.rest()).collect::<Vec<_>>(), ["first line", "", "  12, x7"]);
+
+        cur = lines[2].trim();
+        assert_eq!(cur.position(), (3, 3));
+        assert_eq!(cur.number::<u8>().unwrap(), 12);
+        cur.expect(", ").unwrap();
+        assert_eq!(position(cur.number::<u8>().unwrap_err()), (3, 7, 1));
+        assert_eq!(position(cur.expect("y").unwrap_err()), (3, 7, 1));
+
+        let field = cur.take_while(|c| c.is_alphanumeric());
+        assert_eq!(position(cur.parse_at::<u8>(field).unwrap_err()), (3, 7, 2));
+        assert_eq!(position(cur.error("Missing")), (3, 9, 0));
+        assert_eq!(position(lines[0].error_at("elsewhere", "Wrong")), (1, 1, 1));
+    }
+
+    #[test]
+    fn blocks() {
+        let mut cur = Cursor::new("\n\na\nb\n\n\nc\n");
+        assert_eq!(cur.next_block().unwrap().rest(), "a\nb");
+        let block = cur.next_block().unwrap();
+        assert_eq!((block.rest(), block.position()), ("c", (7, 1)));
+        assert!(cur.next_block().is_none());
+    }
+
+    #[test]
+    fn snippet() {
+        let mut line = Cursor::new("ab\n\tcd ef\n").lines().nth(1).unwrap();
+        line.take_until(" ");
+        let err = line.parse::<u8>().unwrap_err();
+        assert_eq!(err.to_string(), "Line 2, column 5: Invalid value 'ef': invalid digit found in string");
+        assert_eq!(find_error(&err).unwrap().snippet(), "  |\n2 | \tcd ef\n  | \t   ^^");
+    }
+}
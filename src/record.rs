@@ -0,0 +1,630 @@
+use anyhow::Result;
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::grid::Grid;
+
+/// Receiver for frames drawn by a simulation as it runs
+///
+/// Frames are grids of characters, much like the puzzle's own drawings. They can be different
+/// sizes, and are placed by the position of their top-left cell in the simulation's coordinates.
+pub trait Recorder {
+    /// Called before each frame is drawn, which is skipped if this returns `false`
+    fn wants_frame(&mut self) -> bool {
+        true
+    }
+
+    /// Record a frame
+    fn frame(&mut self, origin: (isize, isize), cells: Grid<char>);
+}
+
+/// Record a frame if the recorder wants one, only drawing it if so
+pub fn record_with<F: FnOnce() -> ((isize, isize), Grid<char>)>(
+    rec: &mut Option<&mut dyn Recorder>,
+    draw: F,
+) {
+    if let Some(rec) = rec.as_deref_mut() {
+        if rec.wants_frame() {
+            let (origin, cells) = draw();
+            rec.frame(origin, cells);
+        }
+    }
+}
+
+/// Frames collected from a simulation, ready to be exported
+pub struct Recording {
+    frames: Vec<((isize, isize), Grid<char>)>,
+    max_frames: usize,
+    dropped: usize,
+}
+
+impl Recorder for Recording {
+    fn wants_frame(&mut self) -> bool {
+        let wanted = self.frames.len() < self.max_frames;
+        if !wanted {
+            self.dropped += 1;
+        }
+        wanted
+    }
+
+    fn frame(&mut self, origin: (isize, isize), cells: Grid<char>) {
+        self.frames.push((origin, cells));
+    }
+}
+
+/// File format to export a recording as
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Format {
+    Gif,
+    Apng,
+
+    /// A directory of numbered PPM images
+    Ppm,
+
+    /// An asciinema recording, which plays the frames back as text
+    Cast,
+}
+
+impl Format {
+    /// Pick a format from a path's extension, where anything unknown is a directory of images
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gif") => Self::Gif,
+            Some("png" | "apng") => Self::Apng,
+            Some("cast") => Self::Cast,
+            _ => Self::Ppm,
+        }
+    }
+}
+
+/// Settings for exporting a recording
+#[derive(Copy, Clone, Debug)]
+pub struct ExportOptions {
+    /// Width and height of each cell, in pixels
+    pub cell_size: usize,
+
+    /// Frames per second
+    pub fps: f64,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self { cell_size: 4, fps: 10.0 }
+    }
+}
+
+impl Recording {
+    /// Start a recording which keeps up to `max_frames` frames
+    pub fn new(max_frames: usize) -> Self {
+        Self { frames: Vec::new(), max_frames, dropped: 0 }
+    }
+
+    /// Number of frames kept
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Number of frames which were dropped after reaching the limit
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Place every frame on a canvas big enough for all of them, with empty space as `' '`
+    fn canvases(&self) -> Vec<Grid<char>> {
+        let min_x = self.frames.iter().map(|(o, _)| o.0).min().unwrap_or(0);
+        let min_y = self.frames.iter().map(|(o, _)| o.1).min().unwrap_or(0);
+        let max_x = self.frames.iter().map(|(o, g)| o.0 + g.width() as isize).max().unwrap_or(0);
+        let max_y = self.frames.iter().map(|(o, g)| o.1 + g.height() as isize).max().unwrap_or(0);
+        let (width, height) = ((max_x - min_x) as usize, (max_y - min_y) as usize);
+
+        self.frames.iter()
+            .map(|(origin, cells)| {
+                let (dx, dy) = ((origin.0 - min_x) as usize, (origin.1 - min_y) as usize);
+                Grid::from_fn(width, height, |x, y| {
+                    match (x.checked_sub(dx), y.checked_sub(dy)) {
+                        (Some(x), Some(y)) => cells.try_get((x, y)).copied().unwrap_or(' '),
+                        _ => ' ',
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Write the recording to a file, or a directory for [`Format::Ppm`]
+    pub fn export(&self, path: &Path, format: Format, opts: &ExportOptions) -> Result<()> {
+        anyhow::ensure!(!self.frames.is_empty(), "No frames were recorded");
+        anyhow::ensure!(opts.cell_size > 0 && opts.fps > 0.0, "Invalid export settings");
+
+        let frames = self.canvases();
+        let palette = Palette::new(&frames);
+        match format {
+            Format::Gif => write_gif(path, &frames, &palette, opts),
+            Format::Apng => write_apng(path, &frames, &palette, opts),
+            Format::Ppm => write_ppm(path, &frames, &palette, opts),
+            Format::Cast => write_cast(path, &frames, opts),
+        }
+    }
+}
+
+/// Colours used by the frames of a recording, in order of first use
+struct Palette {
+    chars: Vec<char>,
+}
+
+impl Palette {
+    fn new(frames: &[Grid<char>]) -> Self {
+        let mut chars = vec![' '];
+        for c in frames.iter().flat_map(|f| f.cells()) {
+            if !chars.contains(c) {
+                chars.push(*c);
+            }
+        }
+        Self { chars }
+    }
+
+    /// Colour for the cells drawn with a character
+    ///
+    /// The usual puzzle characters have fixed colours, and anything else gets one of a set of
+    /// bright colours.
+    fn color(c: char) -> [u8; 3] {
+        const OTHERS: &[[u8; 3]] = &[
+            [230, 120, 200], [120, 220, 220], [200, 160, 255], [255, 170, 90],
+            [170, 230, 110], [255, 130, 130], [130, 170, 255], [240, 240, 140],
+        ];
+
+        match c {
+            ' ' | '.' => [20, 20, 40],
+            '#' => [130, 130, 140],
+            '|' | '-' | '+' => [90, 90, 100],
+            'o' | '~' => [230, 200, 90],
+            '@' | 'H' => [230, 70, 60],
+            'T' | 'E' => [80, 220, 100],
+            '<' | '>' | '^' | 'v' => [140, 190, 255],
+            '1'..='9' => [255, 255 - 20 * (c as u8 - b'0'), 80],
+            _ => OTHERS[c as usize % OTHERS.len()],
+        }
+    }
+
+    fn index(&self, c: char) -> u8 {
+        self.chars.iter().position(|x| *x == c).unwrap_or(0) as u8
+    }
+
+    /// Colour table, with one entry per character in use
+    fn rgb(&self) -> Vec<[u8; 3]> {
+        self.chars.iter().map(|c| Self::color(*c)).collect()
+    }
+}
+
+/// Scale a frame up to pixels, as indices into the palette
+fn pixels(frame: &Grid<char>, palette: &Palette, cell_size: usize) -> Vec<u8> {
+    let width = frame.width() * cell_size;
+    let mut out = Vec::with_capacity(width * frame.height() * cell_size);
+    for y in 0..frame.height() {
+        let row = frame.row_iter(y)
+                 .flat_map(|c| std::iter::repeat_n(palette.index(*c), cell_size))
+                 .collect::<Vec<_>>();
+        for _ in 0..cell_size {
+            out.extend_from_slice(&row);
+        }
+    }
+    out
+}
+
+fn write_ppm(dir: &Path, frames: &[Grid<char>], palette: &Palette, opts: &ExportOptions) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let colors = palette.rgb();
+    for (i, frame) in frames.iter().enumerate() {
+        let (width, height) = (frame.width() * opts.cell_size, frame.height() * opts.cell_size);
+        let mut data = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        for px in pixels(frame, palette, opts.cell_size) {
+            data.extend_from_slice(&colors[px as usize]);
+        }
+        std::fs::write(dir.join(format!("frame{:05}.ppm", i)), data)?;
+    }
+    Ok(())
+}
+
+fn write_cast(path: &Path, frames: &[Grid<char>], opts: &ExportOptions) -> Result<()> {
+    fn json_str(s: &str) -> String {
+        let mut out = String::from("\"");
+        for c in s.chars() {
+            match c {
+                '"' => out += "\\\"",
+                '\\' => out += "\\\\",
+                c if (c as u32) < 0x20 => out += &format!("\\u{:04x}", c as u32),
+                c => out.push(c),
+            }
+        }
+        out + "\""
+    }
+
+    let (width, height) = (frames[0].width(), frames[0].height());
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    writeln!(out, "{{\"version\": 2, \"width\": {}, \"height\": {}}}", width, height)?;
+    for (i, frame) in frames.iter().enumerate() {
+        // clear the screen and redraw from the top-left corner each time
+        let text = frame.render_with(|c| *c).replace('\n', "\r\n");
+        let text = format!("\x1b[H\x1b[J{}", text.trim_end());
+        writeln!(out, "[{:.3}, \"o\", {}]", i as f64 / opts.fps, json_str(&text))?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Writer for a stream of variable-length codes, least significant bit first
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, bits: u32) {
+        self.acc |= value << self.bits;
+        self.bits += bits;
+        while self.bits >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    /// Write a Huffman code, which is stored most significant bit first
+    fn write_code(&mut self, code: u32, bits: u32) {
+        self.write(code.reverse_bits() >> (32 - bits), bits);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.out.push(self.acc as u8);
+        }
+        self.out
+    }
+}
+
+/// Compress palette indices with GIF's variant of LZW
+fn lzw(data: &[u8], min_size: u32) -> Vec<u8> {
+    let clear = 1u32 << min_size;
+    let mut out = BitWriter::default();
+    let mut table = std::collections::HashMap::new();
+    let mut size = min_size + 1;
+    let mut next = clear + 2;
+    out.write(clear, size);
+
+    let Some((first, rest)) = data.split_first() else {
+        out.write(clear + 1, size);
+        return out.finish();
+    };
+    let mut code = *first as u32;
+    for px in rest {
+        if let Some(c) = table.get(&(code, *px)) {
+            code = *c;
+            continue;
+        }
+
+        out.write(code, size);
+        if next == 4096 {
+            // the table is full, so start again
+            out.write(clear, size);
+            table.clear();
+            size = min_size + 1;
+            next = clear + 2;
+        } else {
+            if next >= 1 << size {
+                size += 1;
+            }
+            table.insert((code, *px), next);
+            next += 1;
+        }
+        code = *px as u32;
+    }
+    out.write(code, size);
+
+    // the decoder adds an entry for the last code too, which can widen the end code
+    if next == 1 << size && size < 12 {
+        size += 1;
+    }
+    out.write(clear + 1, size);
+    out.finish()
+}
+
+fn write_gif(path: &Path, frames: &[Grid<char>], palette: &Palette, opts: &ExportOptions) -> Result<()> {
+    let colors = palette.rgb();
+    anyhow::ensure!(colors.len() <= 256, "Too many colours for a GIF");
+    let (width, height) = (frames[0].width() * opts.cell_size, frames[0].height() * opts.cell_size);
+    anyhow::ensure!(width <= 0xffff && height <= 0xffff, "Frames are too large for a GIF");
+
+    // colour tables have a power of two size, of at least 4 entries
+    let bits = (colors.len().next_power_of_two().trailing_zeros()).max(2);
+    let delay = (100.0 / opts.fps).round().max(1.0) as u16;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"GIF89a");
+    out.extend_from_slice(&(width as u16).to_le_bytes());
+    out.extend_from_slice(&(height as u16).to_le_bytes());
+    out.extend_from_slice(&[0xf0 | (bits as u8 - 1), 0, 0]);
+    for i in 0..1 << bits {
+        out.extend_from_slice(colors.get(i).unwrap_or(&[0, 0, 0]));
+    }
+    // loop forever
+    out.extend_from_slice(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+    for frame in frames {
+        out.extend_from_slice(&[0x21, 0xf9, 4, 0]);
+        out.extend_from_slice(&delay.to_le_bytes());
+        out.extend_from_slice(&[0, 0, 0x2c, 0, 0, 0, 0]);
+        out.extend_from_slice(&(width as u16).to_le_bytes());
+        out.extend_from_slice(&(height as u16).to_le_bytes());
+        out.extend_from_slice(&[0, bits as u8]);
+
+        for block in lzw(&pixels(frame, palette, opts.cell_size), bits).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend_from_slice(block);
+        }
+        out.push(0);
+    }
+    out.push(0x3b);
+
+    std::fs::write(path, out)?;
+    Ok(())
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Compress data as a zlib stream, using deflate with the fixed Huffman codes
+///
+/// Matches are found greedily through short hash chains, which does well enough on the long runs
+/// and repeated rows of scaled-up frames.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    const LENGTHS: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51,
+                                59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+    const LENGTH_BITS: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4,
+                                   4, 4, 5, 5, 5, 5, 0];
+    const DISTS: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385,
+                              513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385,
+                              24577];
+    const DIST_BITS: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9,
+                                 10, 10, 11, 11, 12, 12, 13, 13];
+    const WINDOW: usize = 32768;
+    const CHAIN: usize = 16;
+
+    fn literal(out: &mut BitWriter, sym: u32) {
+        match sym {
+            0..=143 => out.write_code(0x30 + sym, 8),
+            144..=255 => out.write_code(0x190 + sym - 144, 9),
+            256..=279 => out.write_code(sym - 256, 7),
+            _ => out.write_code(0xc0 + sym - 280, 8),
+        }
+    }
+
+    let mut out = BitWriter::default();
+    out.write(1, 1); // final block
+    out.write(1, 2); // fixed codes
+
+    let hash = |i: usize| {
+        ((data[i] as usize) << 10 ^ (data[i + 1] as usize) << 5 ^ data[i + 2] as usize) & 0x7fff
+    };
+    let mut head = vec![usize::MAX; 0x8000];
+    let mut prev = vec![usize::MAX; WINDOW];
+    let insert = |i: usize, head: &mut [usize], prev: &mut [usize]| {
+        if i + 2 < data.len() {
+            let h = hash(i);
+            prev[i % WINDOW] = head[h];
+            head[h] = i;
+        }
+    };
+
+    let mut i = 0;
+    while i < data.len() {
+        // find the longest match among recent positions with the same hash
+        let mut best = (0, 0);
+        if i + 2 < data.len() {
+            let mut cand = head[hash(i)];
+            for _ in 0..CHAIN {
+                if cand == usize::MAX || i - cand > WINDOW {
+                    break;
+                }
+                let len = data[cand..].iter().zip(&data[i..]).take(258).take_while(|(a, b)| a == b).count();
+                if len > best.0 {
+                    best = (len, i - cand);
+                }
+                let next = prev[cand % WINDOW];
+                if next == usize::MAX || next >= cand {
+                    break;
+                }
+                cand = next;
+            }
+        }
+
+        if best.0 >= 3 {
+            let (len, dist) = best;
+            let l = LENGTHS.iter().rposition(|b| *b as usize <= len).unwrap();
+            literal(&mut out, 257 + l as u32);
+            out.write((len - LENGTHS[l] as usize) as u32, LENGTH_BITS[l] as u32);
+            let d = DISTS.iter().rposition(|b| *b as usize <= dist).unwrap();
+            out.write_code(d as u32, 5);
+            out.write((dist - DISTS[d] as usize) as u32, DIST_BITS[d] as u32);
+
+            for j in i..i + len {
+                insert(j, &mut head, &mut prev);
+            }
+            i += len;
+        } else {
+            literal(&mut out, data[i] as u32);
+            insert(i, &mut head, &mut prev);
+            i += 1;
+        }
+    }
+    literal(&mut out, 256);
+
+    // adler-32 checksum of the uncompressed data
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    let mut stream = vec![0x78, 0x01];
+    stream.extend(out.finish());
+    stream.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    stream
+}
+
+fn write_apng(path: &Path, frames: &[Grid<char>], palette: &Palette, opts: &ExportOptions) -> Result<()> {
+    fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = out.len();
+        out.extend_from_slice(kind);
+        out.extend_from_slice(data);
+        let crc = crc32(&out[start..]);
+        out.extend_from_slice(&crc.to_be_bytes());
+    }
+
+    let colors = palette.rgb();
+    anyhow::ensure!(colors.len() <= 256, "Too many colours for a PNG");
+    let (width, height) = (frames[0].width() * opts.cell_size, frames[0].height() * opts.cell_size);
+    let size = [(width as u32).to_be_bytes(), (height as u32).to_be_bytes()].concat();
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    // 8-bit palette indices
+    chunk(&mut out, b"IHDR", &[&size[..], &[8, 3, 0, 0, 0]].concat());
+    chunk(&mut out, b"PLTE", &colors.concat());
+    chunk(&mut out, b"acTL", &[(frames.len() as u32).to_be_bytes(), 0u32.to_be_bytes()].concat());
+
+    // frame delays are a fraction of a second
+    let (delay_num, delay_den) = ((1000.0 / opts.fps).round().max(1.0) as u16, 1000u16);
+    let mut seq = 0u32;
+    for (i, frame) in frames.iter().enumerate() {
+        let mut fctl = seq.to_be_bytes().to_vec();
+        fctl.extend_from_slice(&size);
+        fctl.extend_from_slice(&[0; 8]);
+        fctl.extend_from_slice(&delay_num.to_be_bytes());
+        fctl.extend_from_slice(&delay_den.to_be_bytes());
+        fctl.extend_from_slice(&[0, 0]);
+        chunk(&mut out, b"fcTL", &fctl);
+        seq += 1;
+
+        // each row starts with its filter type, which is always none here
+        let data = pixels(frame, palette, opts.cell_size).chunks(width)
+                   .flat_map(|row| std::iter::once(0).chain(row.iter().copied()))
+                   .collect::<Vec<_>>();
+        let data = zlib(&data);
+        if i == 0 {
+            chunk(&mut out, b"IDAT", &data);
+        } else {
+            chunk(&mut out, b"fdAT", &[&seq.to_be_bytes()[..], &data].concat());
+            seq += 1;
+        }
+    }
+    chunk(&mut out, b"IEND", &[]);
+
+    std::fs::write(path, out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+
+        // a single literal, with the adler-32 of "a" at the end
+        let z = zlib(b"a");
+        assert_eq!(&z[..2], &[0x78, 0x01]);
+        assert_eq!(&z[z.len() - 4..], &0x00620062u32.to_be_bytes());
+    }
+
+    /// Decompress GIF's LZW, checking that the end code is at the very end
+    fn unlzw(data: &[u8], min_size: u32) -> Vec<u8> {
+        let clear = 1u32 << min_size;
+        let mut pos = 0;
+        let mut read = |bits: u32| (0..bits).fold(0, |code, i| {
+            let bit = (data[pos / 8] >> (pos % 8)) & 1;
+            pos += 1;
+            code | (bit as u32) << i
+        });
+
+        let mut out = Vec::new();
+        let mut table = Vec::<Vec<u8>>::new();
+        let mut size = min_size + 1;
+        let mut prev: Option<Vec<u8>> = None;
+        loop {
+            let code = read(size);
+            if code == clear {
+                table = (0..clear + 2).map(|c| vec![c as u8]).collect();
+                size = min_size + 1;
+                prev = None;
+                continue;
+            } else if code == clear + 1 {
+                break;
+            }
+
+            let entry = match table.get(code as usize) {
+                Some(entry) => entry.clone(),
+                None => {
+                    let mut entry = prev.clone().unwrap();
+                    entry.push(entry[0]);
+                    entry
+                }
+            };
+            if let Some(mut prev) = prev {
+                prev.push(entry[0]);
+                table.push(prev);
+            }
+            out.extend_from_slice(&entry);
+            prev = Some(entry);
+            if table.len() == 1 << size && size < 12 {
+                size += 1;
+            }
+        }
+        assert_eq!(pos.div_ceil(8), data.len());
+        out
+    }
+
+    #[test]
+    fn lzw_round_trip() {
+        let mut x = 1u32;
+        let data = (0..20000).map(|_| {
+            x = x.wrapping_mul(1103515245).wrapping_add(12345);
+            (x >> 16) as u8 % 3
+        }).collect::<Vec<_>>();
+
+        // every short prefix, so that some end just as the code width grows
+        for len in 0..600 {
+            assert_eq!(unlzw(&lzw(&data[..len], 2), 2), &data[..len]);
+        }
+        // and enough to fill the table
+        assert_eq!(unlzw(&lzw(&data, 2), 2), data);
+        assert_eq!(unlzw(&lzw(&[7; 5000], 3), 3), [7; 5000]);
+    }
+
+    #[test]
+    fn canvas() {
+        let mut rec = Recording::new(2);
+        let mut target = Some(&mut rec as &mut dyn Recorder);
+        record_with(&mut target, || ((0, 0), Grid::filled(2, 1, '#')));
+        record_with(&mut target, || ((-1, 1), Grid::filled(1, 1, 'o')));
+        record_with(&mut target, || unreachable!());
+        assert_eq!((rec.len(), rec.dropped()), (2, 1));
+
+        let frames = rec.canvases().iter().map(|f| f.render_with(|c| *c)).collect::<Vec<_>>();
+        assert_eq!(frames, [" ##\n   \n", "   \no  \n"]);
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::Result;
+
+use std::io::Write;
+use std::time::Instant;
+
+use aoc2022::parse;
+use aoc2022::solution::{Inspect, Loaded};
+
+use crate::guard;
+
+const HELP: &str = "\
+Commands:
+  part1, part2    Solve a part
+  inspect [PART]  Start stepping through a part from the beginning (default: part 1)
+  step [N]        Advance the inspected part by N steps (default: 1), then show it
+  show            Show the state of the inspected part
+  reload          Read and parse the input again
+  help            Show this message
+  quit            Leave the REPL, as does the end of input";
+
+/// A part being stepped through
+struct Inspecting<'a> {
+    part: usize,
+    state: Box<dyn Inspect + 'a>,
+    steps: usize,
+}
+
+/// Print an error from a command, with a snippet of the input if it's a parse error
+fn report_error(e: &anyhow::Error) {
+    eprintln!("error: {:#}", e);
+    if let Some(err) = parse::find_error(e) {
+        eprintln!("{}", err.snippet());
+    }
+}
+
+/// Parse an optional numeric argument to a command
+fn count_arg(arg: Option<&str>, default: usize) -> Result<usize> {
+    arg.map_or(Ok(default), |a| a.parse().map_err(|_| anyhow::anyhow!("Invalid count '{}'", a)))
+}
+
+/// Show the state of a part being inspected, which might panic like any other solver code
+fn show(insp: &Inspecting) -> Result<()> {
+    let text = guard::catch(|| Ok(insp.state.show()))?;
+    println!("{}", text.trim_end());
+    Ok(())
+}
+
+/// Handle one command, returning `false` if the session is over for this input
+fn command<'a>(
+    loaded: Option<&'a dyn Loaded>,
+    inspecting: &mut Option<Inspecting<'a>>,
+    cmd: &str,
+    arg: Option<&str>,
+) -> Result<bool> {
+    let loaded = || loaded.ok_or_else(|| anyhow::anyhow!("No input loaded (try 'reload')"));
+    let start = |part: usize| -> Result<Inspecting<'a>> {
+        let state = guard::catch(|| loaded()?.inspect(part))?;
+        Ok(Inspecting { part, state, steps: 0 })
+    };
+
+    match cmd {
+        "part1" | "part2" => {
+            let part = if cmd == "part1" { 1 } else { 2 };
+            let begin = Instant::now();
+            let answer = guard::catch(|| loaded()?.solve(part))?;
+            println!("Part {}: {} ({:.1} ms)", part, answer, begin.elapsed().as_secs_f64() * 1000.0);
+        }
+        "inspect" => {
+            let insp = inspecting.insert(start(count_arg(arg, 1)?)?);
+            show(insp)?;
+        }
+        "step" => {
+            let n = count_arg(arg, 1)?;
+            let insp = match inspecting {
+                Some(x) => x,
+                None => {
+                    let insp = inspecting.insert(start(1)?);
+                    println!("Inspecting part 1");
+                    insp
+                }
+            };
+
+            let mut taken = 0;
+            while taken < n && guard::catch(|| insp.state.step())? {
+                taken += 1;
+            }
+            insp.steps += taken;
+            show(insp)?;
+            if taken < n {
+                println!("Part {} finished after {} steps", insp.part, insp.steps);
+            }
+        }
+        "show" => {
+            let insp = inspecting.as_ref()
+                      .ok_or_else(|| anyhow::anyhow!("Not inspecting anything (try 'inspect')"))?;
+            show(insp)?;
+        }
+        "reload" | "quit" | "exit" => return Ok(false),
+        "help" => println!("{}", HELP),
+        _ => anyhow::bail!("Unknown command '{}' (try 'help')", cmd),
+    }
+
+    Ok(true)
+}
+
+/// Run an interactive session exploring a problem's input, reading commands from stdin
+///
+/// The input is loaded with the given function at the start, and again on `reload`. Errors and
+/// panics from solvers are reported without ending the session.
+pub fn run<F: FnMut() -> Result<Box<dyn Loaded>>>(p_num: usize, mut load: F) -> Result<()> {
+    let mut lines = std::io::stdin().lines();
+    println!("Type 'help' for a list of commands");
+
+    loop {
+        let begin = Instant::now();
+        let loaded = match guard::catch(&mut load) {
+            Ok(x) => {
+                println!("Loaded input in {:.1} ms", begin.elapsed().as_secs_f64() * 1000.0);
+                Some(x)
+            }
+            Err(e) => {
+                report_error(&e.context("Failed to load input"));
+                None
+            }
+        };
+
+        let mut inspecting = None;
+        loop {
+            print!("{:02}> ", p_num);
+            std::io::stdout().flush()?;
+
+            let Some(line) = lines.next() else {
+                println!();
+                return Ok(());
+            };
+            let line = line?;
+            let mut words = line.split_whitespace();
+            let Some(cmd) = words.next() else {
+                continue;
+            };
+
+            match command(loaded.as_deref(), &mut inspecting, cmd, words.next()) {
+                Ok(true) => {}
+                Ok(false) if cmd == "reload" => break,
+                Ok(false) => return Ok(()),
+                Err(e) => report_error(&e),
+            }
+        }
+    }
+}
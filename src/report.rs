@@ -0,0 +1,406 @@
+use anyhow::Result;
+
+use std::time::Duration;
+
+use aoc2022::answer::Answer;
+use aoc2022::answers::{Answers, Verdict, DEFAULT_DATASET};
+use aoc2022::parse;
+use aoc2022::solution;
+
+use crate::mem::AllocStats;
+
+/// Output format for run results
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Format {
+    /// Human-readable lines, e.g. `05p1: CMZ`
+    Text,
+
+    /// One JSON object per line, for each problem part
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(anyhow::anyhow!("Unknown output format '{}'", s)),
+        }
+    }
+}
+
+/// Outcome of solving a single part of a problem
+pub struct PartResult {
+    pub part: usize,
+
+    /// Which implementation of the part was run
+    pub variant: &'static str,
+
+    /// Answer, or the error the solver returned
+    pub answer: Result<Answer>,
+
+    /// Time spent in the solver
+    pub time: Duration,
+
+    /// Allocations made by the solver, if they were counted
+    pub alloc: Option<AllocStats>,
+}
+
+/// Outcome of running a single problem
+pub struct ProblemResult {
+    pub problem: usize,
+
+    /// Name of the input dataset which was used
+    pub dataset: String,
+
+    /// Time spent parsing the input, if parsing was attempted
+    pub parse_time: Option<Duration>,
+
+    /// Allocations made while parsing the input, if they were counted
+    pub parse_alloc: Option<AllocStats>,
+
+    /// Results for each part, or the error encountered while reading the input
+    pub parts: Result<Vec<PartResult>>,
+}
+
+/// Writes run results to stdout, optionally checking them against known answers
+pub struct Reporter<'a> {
+    format: Format,
+    answers: Option<&'a Answers>,
+
+    /// Whether to print answers in text mode, rather than just failures
+    show_answers: bool,
+
+    /// Whether to print stage timings in text mode
+    show_times: bool,
+
+    /// Whether to print the drawings behind picture answers in text mode
+    show_art: bool,
+
+    pub passed: usize,
+    pub failed: usize,
+    pub missing: usize,
+}
+
+impl<'a> Reporter<'a> {
+    pub fn new(format: Format, answers: Option<&'a Answers>, show_answers: bool) -> Self {
+        Self {
+            format, answers, show_answers,
+            show_times: false, show_art: false,
+            passed: 0, failed: 0, missing: 0,
+        }
+    }
+
+    /// Also print how long each stage took, in text mode
+    pub fn with_times(self) -> Self {
+        Self { show_times: true, ..self }
+    }
+
+    /// Whether to print the drawings behind picture answers, as well as the text read from them
+    pub fn with_art(self, show_art: bool) -> Self {
+        Self { show_art, ..self }
+    }
+
+    /// Whether answers are being verified
+    pub fn verifying(&self) -> bool {
+        self.answers.is_some()
+    }
+
+    /// Report the results of a single problem
+    pub fn report(&mut self, res: &ProblemResult) {
+        let p_num = res.problem;
+        let dataset = Some(res.dataset.as_str()).filter(|d| *d != DEFAULT_DATASET);
+        let parts = match &res.parts {
+            Ok(x) => x,
+            Err(e) => {
+                // missing inputs aren't a verification failure, but unparseable ones are
+                if self.verifying() && res.parse_time.is_some() {
+                    self.failed += 1;
+                }
+                match self.format {
+                    Format::Text => {
+                        eprintln!("{:02}{}: {:#}", p_num, suffix(dataset), e);
+                        if let Some(err) = parse::find_error(e) {
+                            eprintln!("{}", err.snippet());
+                        }
+                    }
+                    Format::Json => println!("{}", JsonRecord {
+                        problem: p_num,
+                        dataset,
+                        part: None,
+                        variant: None,
+                        answer: None,
+                        error: Some(&format!("{:#}", e)),
+                        parse_time: res.parse_time,
+                        solve_time: None,
+                        parse_alloc: res.parse_alloc.as_ref(),
+                        alloc: None,
+                        verdict: None,
+                    }),
+                }
+                return;
+            }
+        };
+
+        if self.format == Format::Text && (self.show_times || res.parse_alloc.is_some()) {
+            println!("{:02}{}: parsed input{}",
+                     p_num, suffix(dataset), self.stats(res.parse_time, res.parse_alloc.as_ref()));
+        }
+
+        for part in parts {
+            let verdict = match (&part.answer, self.answers) {
+                (Ok(answer), Some(answers)) => {
+                    Some(answers.check(p_num, &res.dataset, part.part, answer))
+                }
+                _ => None,
+            };
+            match (&verdict, &part.answer) {
+                (Some(Verdict::Pass), _) => self.passed += 1,
+                (Some(Verdict::Fail { .. }), _) => self.failed += 1,
+                (Some(Verdict::Missing), _) => self.missing += 1,
+                (None, Err(_)) if self.verifying() => self.failed += 1,
+                (None, _) => {}
+            }
+
+            match self.format {
+                Format::Text => self.write_text(p_num, dataset, part, verdict.as_ref()),
+                Format::Json => println!("{}", JsonRecord {
+                    problem: p_num,
+                    dataset,
+                    part: Some(part.part),
+                    variant: Some(part.variant),
+                    answer: part.answer.as_ref().ok(),
+                    error: part.answer.as_ref().err().map(|e| format!("{:#}", e)).as_deref(),
+                    parse_time: res.parse_time,
+                    solve_time: Some(part.time),
+                    parse_alloc: res.parse_alloc.as_ref(),
+                    alloc: part.alloc.as_ref(),
+                    verdict: verdict.as_ref(),
+                }),
+            }
+        }
+    }
+
+    fn write_text(&self, p_num: usize, dataset: Option<&str>, part: &PartResult,
+                  verdict: Option<&Verdict>) {
+        self.write_verdict(p_num, dataset, part, verdict);
+        let shown = self.show_answers || verdict.is_some();
+        if let (Ok(answer), true, true) = (&part.answer, shown, self.show_art) {
+            if let Some(art) = answer.art().filter(|art| *art != answer.to_string()) {
+                println!("{}", art.trim_end_matches('\n'));
+            }
+        }
+    }
+
+    fn write_verdict(&self, p_num: usize, dataset: Option<&str>, part: &PartResult,
+                     verdict: Option<&Verdict>) {
+        let time = self.stats(Some(part.time), part.alloc.as_ref());
+        let label = match part.variant {
+            solution::DEFAULT_VARIANT => part.part.to_string(),
+            v => format!("{} ({})", part.part, v),
+        } + &suffix(dataset);
+        let answer = match &part.answer {
+            Ok(x) => x.to_string(),
+            Err(e) if self.verifying() => {
+                println!("{:02}p{}: FAIL (solver error: {}){}", p_num, label, e, time);
+                return;
+            }
+            Err(e) => {
+                eprintln!("{:02}: Part {} failed: {}{}", p_num, label, e, time);
+                return;
+            }
+        };
+
+        match verdict {
+            None if self.show_answers => println!("{:02}p{}: {}{}", p_num, label, answer, time),
+            None => {}
+            Some(Verdict::Pass) => println!("{:02}p{}: PASS{}", p_num, label, time),
+            Some(Verdict::Fail { expected }) => {
+                println!("{:02}p{}: FAIL (expected {}, got {}){}",
+                         p_num, label, expected.trim(), answer.trim(), time);
+            }
+            Some(Verdict::Missing) => {
+                println!("{:02}p{}: MISSING (got {}){}", p_num, label, answer.trim(), time);
+            }
+        }
+    }
+
+    /// Format the requested timing and allocation statistics for a stage, e.g. ` [1.20ms]`
+    fn stats(&self, time: Option<Duration>, alloc: Option<&AllocStats>) -> String {
+        let time = time.filter(|_| self.show_times).map(|t| format!("{:.2?}", t));
+        let stats = time.into_iter().chain(alloc.map(|a| a.to_string())).collect::<Vec<_>>();
+        if stats.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", stats.join(", "))
+        }
+    }
+
+    /// Print the verification summary, if verifying
+    ///
+    /// Returns `false` if any part failed verification.
+    pub fn finish(&self) -> bool {
+        if !self.verifying() {
+            return true;
+        }
+
+        let summary = format!("{} passed, {} failed, {} missing",
+                              self.passed, self.failed, self.missing);
+        match self.format {
+            Format::Text => println!("{}", summary),
+            Format::Json => eprintln!("{}", summary),
+        }
+
+        self.failed == 0
+    }
+}
+
+/// Label for results from a dataset other than the default one, e.g. ` [example1]`
+fn suffix(dataset: Option<&str>) -> String {
+    dataset.map(|d| format!(" [{}]", d)).unwrap_or_default()
+}
+
+/// Largest integer which every JSON parser can represent exactly
+const MAX_SAFE_INT: u128 = (1 << 53) - 1;
+
+/// A single line of JSON output
+///
+/// Integer answers are written as JSON numbers, unless they're too large for a double to hold
+/// exactly (beyond ±2^53), when they're written as strings of digits instead. Other answers are
+/// strings, and picture answers also include their drawing. The variant and dataset are only included if they aren't the
+/// default ones.
+struct JsonRecord<'a> {
+    problem: usize,
+    dataset: Option<&'a str>,
+    part: Option<usize>,
+    variant: Option<&'a str>,
+    answer: Option<&'a Answer>,
+    error: Option<&'a str>,
+    parse_time: Option<Duration>,
+    solve_time: Option<Duration>,
+    parse_alloc: Option<&'a AllocStats>,
+    alloc: Option<&'a AllocStats>,
+    verdict: Option<&'a Verdict>,
+}
+
+impl std::fmt::Display for JsonRecord<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn opt<T: std::fmt::Display>(x: Option<T>) -> String {
+            x.map(|x| x.to_string()).unwrap_or_else(|| "null".to_owned())
+        }
+
+        let answer = self.answer.map(|a| match a {
+            Answer::Int(n) if n.unsigned_abs() <= MAX_SAFE_INT => n.to_string(),
+            a => JsonStr(&a.to_string()).to_string(),
+        });
+
+        write!(f, "{{\"problem\":{},\"part\":{},\"kind\":{},\"answer\":{},\"error\":{},\
+                   \"parse_ns\":{},\"solve_ns\":{}",
+               self.problem,
+               opt(self.part),
+               opt(self.answer.map(|a| JsonStr(a.kind()))),
+               opt(answer),
+               opt(self.error.map(JsonStr)),
+               opt(self.parse_time.map(|t| t.as_nanos())),
+               opt(self.solve_time.map(|t| t.as_nanos())))?;
+
+        if let Some(v) = self.variant.filter(|v| *v != solution::DEFAULT_VARIANT) {
+            write!(f, ",\"variant\":{}", JsonStr(v))?;
+        }
+        if let Some(d) = self.dataset {
+            write!(f, ",\"dataset\":{}", JsonStr(d))?;
+        }
+        if let Some(art) = self.answer.and_then(|a| a.art()) {
+            write!(f, ",\"art\":{}", JsonStr(&art))?;
+        }
+
+        for (prefix, alloc) in [("parse_", self.parse_alloc), ("", self.alloc)] {
+            if let Some(a) = alloc {
+                write!(f, ",\"{0}allocs\":{1},\"{0}alloc_bytes\":{2},\"{0}peak_bytes\":{3}",
+                       prefix, a.count, a.bytes, a.peak)?;
+            }
+        }
+
+        match self.verdict {
+            None => {}
+            Some(Verdict::Pass) => write!(f, ",\"verdict\":\"pass\"")?,
+            Some(Verdict::Missing) => write!(f, ",\"verdict\":\"missing\"")?,
+            Some(Verdict::Fail { expected }) => {
+                write!(f, ",\"verdict\":\"fail\",\"expected\":{}", JsonStr(expected))?
+            }
+        }
+
+        write!(f, "}}")
+    }
+}
+
+/// A string, formatted as a quoted and escaped JSON string literal
+struct JsonStr<'a>(&'a str);
+
+impl std::fmt::Display for JsonStr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use std::fmt::Write;
+
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json_escaping() {
+        assert_eq!(JsonStr("abc").to_string(), r#""abc""#);
+        assert_eq!(JsonStr("a\"b\\c").to_string(), r#""a\"b\\c""#);
+        assert_eq!(JsonStr("\n# #\n\x01").to_string(), r#""\n# #\n\u0001""#);
+    }
+
+    #[test]
+    fn json_record() {
+        let answer = Answer::from("CMZ");
+        let rec = JsonRecord {
+            problem: 5,
+            dataset: None,
+            part: Some(1),
+            variant: Some(solution::DEFAULT_VARIANT),
+            answer: Some(&answer),
+            error: None,
+            parse_time: Some(Duration::from_nanos(1500)),
+            solve_time: Some(Duration::from_nanos(20)),
+            parse_alloc: None,
+            alloc: None,
+            verdict: Some(&Verdict::Pass),
+        };
+        assert_eq!(rec.to_string(),
+                   r#"{"problem":5,"part":1,"kind":"str","answer":"CMZ","error":null,"parse_ns":1500,"solve_ns":20,"verdict":"pass"}"#);
+
+        let alloc = AllocStats { count: 3, bytes: 120, peak: 64 };
+        let answer = Answer::from(24000);
+        let rec = JsonRecord {
+            answer: Some(&answer), variant: Some("naive"), dataset: Some("example1"),
+            parse_alloc: Some(&alloc), alloc: Some(&alloc), verdict: None, ..rec
+        };
+        assert_eq!(rec.to_string(),
+                   r#"{"problem":5,"part":1,"kind":"int","answer":24000,"error":null,"parse_ns":1500,"solve_ns":20,"variant":"naive","dataset":"example1","parse_allocs":3,"parse_alloc_bytes":120,"parse_peak_bytes":64,"allocs":3,"alloc_bytes":120,"peak_bytes":64}"#);
+
+        // too large for a double
+        let answer = Answer::from(-(1i64 << 53) - 1);
+        let rec = JsonRecord { answer: Some(&answer), variant: None, dataset: None, verdict: None, ..rec };
+        assert!(rec.to_string().contains(r#""kind":"int","answer":"-9007199254740993","#));
+    }
+}
@@ -0,0 +1,187 @@
+use anyhow::{anyhow, Result};
+
+use std::ops::RangeInclusive;
+
+/// A single term of a selection, like `12`, `20-25` or `12:2`
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Term {
+    problems: RangeInclusive<usize>,
+
+    /// Part to restrict the term to, if any
+    part: Option<usize>,
+}
+
+impl Term {
+    fn matches(&self, problem: usize, part: usize) -> bool {
+        self.problems.contains(&problem) && self.part.map(|p| p == part).unwrap_or(true)
+    }
+}
+
+impl std::str::FromStr for Term {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        fn problem_number(s: &str) -> Result<usize> {
+            match s.trim().parse::<usize>() {
+                Ok(0) => Err(anyhow!("Problem numbers are 1-based. Use #1 for the first problem.")),
+                Ok(x) => Ok(x),
+                Err(_) => Err(anyhow!("Unable to parse problem number '{}'", s)),
+            }
+        }
+
+        let (problems, part) = match s.split_once(':') {
+            Some((problems, part)) => {
+                let part = part.trim().parse::<usize>()
+                          .ok()
+                          .filter(|p| (1..=2).contains(p))
+                          .ok_or_else(|| anyhow!("Invalid part '{}'", part))?;
+                (problems, Some(part))
+            }
+            None => (s, None),
+        };
+
+        let problems = match problems.split_once('-') {
+            Some((a, b)) => {
+                let (a, b) = (problem_number(a)?, problem_number(b)?);
+                anyhow::ensure!(a <= b, "Empty problem range '{}'", problems);
+                a..=b
+            }
+            None => {
+                let n = problem_number(problems)?;
+                n..=n
+            }
+        };
+
+        Ok(Self { problems, part })
+    }
+}
+
+/// A set of problems and parts to run
+///
+/// Selections are written as comma-separated terms, each of which is either a problem number or
+/// an inclusive range of them, optionally followed by a part number: `1-5,12:2,20-25`. Terms can
+/// also be excluded with [`Selection::skip`].
+#[derive(Clone, Debug, Default)]
+pub struct Selection {
+    /// Included terms; if empty, everything is included
+    include: Vec<Term>,
+
+    /// Excluded terms, which override included ones
+    exclude: Vec<Term>,
+}
+
+impl Selection {
+    /// Select every problem
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Exclude everything matched by another selection's terms
+    pub fn skip(&mut self, other: &Selection) {
+        self.exclude.extend(other.include.iter().cloned());
+    }
+
+    /// Check that every term refers to a problem which exists, given how many parts each problem
+    /// has, and that every part asked for exists too
+    ///
+    /// A range of problems only needs the part to exist for one of them.
+    pub fn validate(&self, parts: &[usize]) -> Result<()> {
+        for term in self.include.iter().chain(self.exclude.iter()) {
+            anyhow::ensure!(*term.problems.end() <= parts.len(),
+                            "Invalid problem number {} (there are {} problems)",
+                            term.problems.end(), parts.len());
+        }
+
+        for term in &self.include {
+            let Some(part) = term.part else { continue };
+            if term.problems.clone().all(|p| parts[p - 1] < part) {
+                let (start, end) = (term.problems.start(), term.problems.end());
+                if start == end {
+                    anyhow::bail!("Problem {} has no part {}", start, part);
+                }
+                anyhow::bail!("Problems {}-{} have no part {}", start, end, part);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether a given part of a problem is selected
+    pub fn includes(&self, problem: usize, part: usize) -> bool {
+        let included = self.include.is_empty() ||
+                       self.include.iter().any(|t| t.matches(problem, part));
+        included && !self.exclude.iter().any(|t| t.matches(problem, part))
+    }
+
+    /// Which of a problem's parts are selected
+    pub fn parts(&self, problem: usize, parts: usize) -> Vec<usize> {
+        (1..=parts).filter(|part| self.includes(problem, *part)).collect()
+    }
+
+    /// List the selected problems out of `1..=count`, in order
+    ///
+    /// A problem is selected if either of its parts is.
+    pub fn problems(&self, count: usize) -> Vec<usize> {
+        (1..=count).filter(|p| self.includes(*p, 1) || self.includes(*p, 2)).collect()
+    }
+}
+
+impl std::str::FromStr for Selection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let include = s.split(',').map(|t| t.parse()).collect::<Result<Vec<_>>>()?;
+        Ok(Self { include, exclude: Vec::new() })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ranges_and_lists() {
+        let sel = "1-5,12,20-25".parse::<Selection>().unwrap();
+        assert_eq!(sel.problems(25), vec![1, 2, 3, 4, 5, 12, 20, 21, 22, 23, 24, 25]);
+        assert_eq!(sel.parts(12, 2), vec![1, 2]);
+    }
+
+    #[test]
+    fn parts() {
+        let sel = "12:2,3".parse::<Selection>().unwrap();
+        assert_eq!(sel.problems(25), vec![3, 12]);
+        assert_eq!(sel.parts(12, 2), vec![2]);
+        assert_eq!(sel.parts(3, 2), vec![1, 2]);
+    }
+
+    #[test]
+    fn skipping() {
+        let mut sel = Selection::all();
+        sel.skip(&"16,19:2".parse().unwrap());
+        let problems = sel.problems(25);
+        assert!(!problems.contains(&16));
+        assert!(problems.contains(&19));
+        assert_eq!(sel.parts(19, 2), vec![1]);
+        assert_eq!(problems.len(), 24);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!("0".parse::<Selection>().is_err());
+        assert!("5-3".parse::<Selection>().is_err());
+        assert!("3:4".parse::<Selection>().is_err());
+        assert!("x".parse::<Selection>().is_err());
+        assert!("1,".parse::<Selection>().is_err());
+        assert!("30".parse::<Selection>().unwrap().validate(&[2; 25]).is_err());
+    }
+
+    #[test]
+    fn missing_parts() {
+        let mut parts = [2; 25];
+        parts[24] = 1;
+        assert!("25:1,24-25:2".parse::<Selection>().unwrap().validate(&parts).is_ok());
+
+        let err = "1,25:2".parse::<Selection>().unwrap().validate(&parts).unwrap_err();
+        assert_eq!(err.to_string(), "Problem 25 has no part 2");
+    }
+}
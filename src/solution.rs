@@ -0,0 +1,303 @@
+use anyhow::{anyhow, Result};
+
+use std::collections::HashMap;
+
+use crate::answer::Answer;
+use crate::gen::Rng;
+use crate::record::Recorder;
+
+/// Description of a named runtime parameter
+#[derive(Copy, Clone, Debug)]
+pub struct ParamInfo {
+    pub name: &'static str,
+
+    /// Default value, as written in the source
+    pub default: &'static str,
+}
+
+/// Description of an alternative implementation of one part of a problem
+#[derive(Copy, Clone, Debug)]
+pub struct VariantInfo {
+    /// The (1-based) part this implements
+    pub part: usize,
+
+    pub name: &'static str,
+}
+
+/// Name of the main implementation of each part, which every part has
+pub const DEFAULT_VARIANT: &str = "default";
+
+/// Values given for named parameters, e.g. by `--param name=value`
+#[derive(Clone, Debug, Default)]
+pub struct ParamValues {
+    values: HashMap<String, String>,
+}
+
+impl ParamValues {
+    /// Set a parameter, replacing any previous value
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.values.insert(name.to_owned(), value.to_owned());
+    }
+
+    /// Names of every parameter which has been given a value
+    pub fn names(&self) -> impl Iterator<Item=&str> {
+        self.values.keys().map(|s| s.as_str())
+    }
+
+    /// Parse the value of a parameter, if one was given
+    pub fn get<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>> {
+        self.values.get(name)
+            .map(|v| v.parse().map_err(|_| anyhow!("Invalid value for parameter {}: '{}'", name, v)))
+            .transpose()
+    }
+
+    /// Add a parameter given as `name=value`
+    pub fn add(&mut self, s: &str) -> Result<()> {
+        let (name, value) = s.split_once('=')
+                           .ok_or_else(|| anyhow!("Expected a parameter as name=value, got '{}'", s))?;
+        anyhow::ensure!(!name.trim().is_empty(), "Missing parameter name in '{}'", s);
+        self.set(name.trim(), value.trim());
+        Ok(())
+    }
+}
+
+/// A solver for a single day's puzzle
+///
+/// Implementations are normally generated by the `problem!` macro from a module's `load_input`
+/// and `solveN` functions, but nothing stops a module from implementing this by hand.
+pub trait Solution {
+    /// Parsed representation of the puzzle input
+    ///
+    /// Inputs are shared between threads when parts are solved concurrently.
+    type Input: Send + Sync + 'static;
+
+    /// Runtime parameters, such as limits which differ between the example and real inputs
+    type Params: Send + Sync + 'static;
+
+    /// Answer produced by the first part
+    type Answer1: Into<Answer> + 'static;
+
+    /// Answer produced by the second part
+    ///
+    /// Problems which only have one part use [`NoAnswer`] here.
+    type Answer2: Into<Answer> + 'static;
+
+    /// How many parts this problem has
+    const PARTS: usize = 2;
+
+    /// Parameters accepted by this problem
+    const PARAMS: &'static [ParamInfo] = &[];
+
+    /// Alternative implementations of the parts, besides the main ones
+    const VARIANTS: &'static [VariantInfo] = &[];
+
+    /// The (1-based) parts whose answers must be positive, if they're integers
+    const POSITIVE: &'static [usize] = &[];
+
+    /// Build the parameters, using defaults for any which aren't given
+    fn parse_params(values: &ParamValues) -> Result<Self::Params>;
+
+    /// Parse the puzzle input
+    fn parse(input: &mut dyn std::io::BufRead) -> Result<Self::Input>;
+
+    /// Solve the first part of the puzzle
+    fn part1(input: &Self::Input, params: &Self::Params) -> Result<Self::Answer1>;
+
+    /// Solve the second part of the puzzle
+    fn part2(input: &Self::Input, params: &Self::Params) -> Result<Self::Answer2>;
+
+    /// Solve a part of the puzzle using one of the alternative implementations in `VARIANTS`
+    #[allow(unused_variables)]
+    fn solve_variant(input: &Self::Input, params: &Self::Params, part: usize, name: &str)
+        -> Result<Answer>
+    {
+        anyhow::bail!("Part {} has no variant '{}'", part, name)
+    }
+
+    /// Generate a random valid input, if this problem has a generator
+    ///
+    /// `scale` is a rough measure of the input's size, where 1 is tiny. Inputs are valid for the
+    /// given parameters, much as the real inputs are valid for the default ones.
+    #[allow(unused_variables)]
+    fn generate(rng: &mut Rng, scale: usize, params: &Self::Params) -> Option<String> {
+        None
+    }
+
+    /// Start stepping through a part of the puzzle, if this problem has state worth looking at
+    #[allow(unused_variables)]
+    fn inspect<'a>(input: &'a Self::Input, params: &'a Self::Params, part: usize)
+        -> Option<Box<dyn Inspect + 'a>>
+    {
+        None
+    }
+
+    /// Run a part of the puzzle while drawing frames to a recorder, if this problem supports it
+    #[allow(unused_variables)]
+    fn record(input: &Self::Input, params: &Self::Params, part: usize, rec: &mut dyn Recorder)
+        -> Option<Result<()>>
+    {
+        None
+    }
+}
+
+/// Intermediate state of a part of a puzzle, which can be stepped through and shown in the REPL
+pub trait Inspect {
+    /// Advance by one step, returning `false` if there was nothing left to do
+    fn step(&mut self) -> Result<bool>;
+
+    /// Describe the current state, usually by drawing it
+    fn show(&self) -> String;
+}
+
+/// Answer type for a part which doesn't exist
+///
+/// This is uninhabited, so a part with this answer type can never succeed.
+#[derive(Debug)]
+pub enum NoAnswer {}
+
+impl From<NoAnswer> for Answer {
+    fn from(x: NoAnswer) -> Self {
+        match x {}
+    }
+}
+
+/// A parsed puzzle input, bundled with the solution that will consume it
+pub trait Loaded: Send + Sync {
+    /// Solve a given (1-based) part of the problem
+    ///
+    /// Returns an error if the part doesn't exist, or if the answer fails [`Answer::check`].
+    fn solve(&self, part: usize) -> Result<Answer> {
+        self.solve_variant(part, DEFAULT_VARIANT)
+    }
+
+    /// Solve a given (1-based) part of the problem with a particular implementation
+    fn solve_variant(&self, part: usize, variant: &str) -> Result<Answer>;
+
+    /// Start stepping through a given (1-based) part of the problem
+    ///
+    /// Returns an error if the part doesn't exist, or if the problem doesn't support this.
+    fn inspect(&self, part: usize) -> Result<Box<dyn Inspect + '_>>;
+
+    /// Run a given (1-based) part of the problem while drawing frames to a recorder
+    ///
+    /// Returns an error if the part doesn't exist, or if the problem doesn't support this.
+    fn record(&self, part: usize, rec: &mut dyn Recorder) -> Result<()>;
+}
+
+struct LoadedInput<S: Solution> {
+    input: S::Input,
+    params: S::Params,
+}
+
+impl<S: Solution> Loaded for LoadedInput<S> {
+    fn solve_variant(&self, part: usize, variant: &str) -> Result<Answer> {
+        anyhow::ensure!(part >= 1 && part <= S::PARTS, "Problem has no part {}", part);
+        let answer: Answer = match part {
+            _ if variant != DEFAULT_VARIANT => {
+                S::solve_variant(&self.input, &self.params, part, variant)?
+            }
+            1 => S::part1(&self.input, &self.params)?.into(),
+            2 => S::part2(&self.input, &self.params)?.into(),
+            _ => unreachable!(),
+        };
+
+        answer.check(S::POSITIVE.contains(&part))?;
+        Ok(answer)
+    }
+
+    fn inspect(&self, part: usize) -> Result<Box<dyn Inspect + '_>> {
+        anyhow::ensure!(part >= 1 && part <= S::PARTS, "Problem has no part {}", part);
+        S::inspect(&self.input, &self.params, part)
+            .ok_or_else(|| anyhow!("Problem has no state to inspect"))
+    }
+
+    fn record(&self, part: usize, rec: &mut dyn Recorder) -> Result<()> {
+        anyhow::ensure!(part >= 1 && part <= S::PARTS, "Problem has no part {}", part);
+        S::record(&self.input, &self.params, part, rec)
+            .unwrap_or_else(|| Err(anyhow!("Problem has no simulation to record")))
+    }
+}
+
+/// Type-erased interface to a [`Solution`], used by the problem registry
+pub trait Problem: Sync {
+    /// How many parts this problem has
+    fn parts(&self) -> usize;
+
+    /// Parameters accepted by this problem
+    fn params(&self) -> &'static [ParamInfo];
+
+    /// Names of the implementations of a part, starting with [`DEFAULT_VARIANT`]
+    fn variants(&self, part: usize) -> Vec<&'static str>;
+
+    /// The implementation of a part to use when `requested` is wanted where it exists
+    ///
+    /// Falls back to [`DEFAULT_VARIANT`] if the part has no such variant.
+    fn pick_variant(&self, part: usize, requested: Option<&str>) -> &'static str {
+        let variants = self.variants(part);
+        requested.and_then(|r| variants.iter().find(|v| **v == r).copied())
+                 .unwrap_or(DEFAULT_VARIANT)
+    }
+
+    /// Parse an input for this problem
+    ///
+    /// Values given for parameters this problem doesn't have are ignored.
+    fn load(&self, input: &mut dyn std::io::BufRead, params: &ParamValues)
+        -> Result<Box<dyn Loaded>>;
+
+    /// Generate a random valid input, if this problem has a generator
+    fn generate(&self, rng: &mut Rng, scale: usize, params: &ParamValues)
+        -> Result<Option<String>>;
+}
+
+impl<S: Solution + Sync + 'static> Problem for S {
+    fn parts(&self) -> usize {
+        S::PARTS
+    }
+
+    fn params(&self) -> &'static [ParamInfo] {
+        S::PARAMS
+    }
+
+    fn variants(&self, part: usize) -> Vec<&'static str> {
+        std::iter::once(DEFAULT_VARIANT)
+            .chain(S::VARIANTS.iter().filter(|v| v.part == part).map(|v| v.name))
+            .collect()
+    }
+
+    fn load(&self, input: &mut dyn std::io::BufRead, params: &ParamValues)
+        -> Result<Box<dyn Loaded>>
+    {
+        let params = S::parse_params(params)?;
+        Ok(Box::new(LoadedInput::<S> { input: S::parse(input)?, params }))
+    }
+
+    fn generate(&self, rng: &mut Rng, scale: usize, params: &ParamValues)
+        -> Result<Option<String>>
+    {
+        Ok(S::generate(rng, scale, &S::parse_params(params)?))
+    }
+}
+
+/// Load an example input and check the answer to one of its parts, panicking on a mismatch
+///
+/// This goes through the same [`Problem`] interface as the runner. A single leading newline is
+/// stripped from the input, so that examples can start on the line after the opening quote.
+/// Answers are compared the same way as in `--verify` mode, and every variant of the part is
+/// checked.
+#[cfg(test)]
+pub fn check_example(
+    problem: &dyn Problem,
+    input: &str,
+    params: &ParamValues,
+    part: usize,
+    expected: &str,
+) {
+    let input = input.strip_prefix('\n').unwrap_or(input);
+    let loaded = problem.load(&mut input.as_bytes(), params).expect("Failed to load example");
+    for variant in problem.variants(part) {
+        let answer = loaded.solve_variant(part, variant).expect("Failed to solve example");
+
+        assert!(answer.matches(expected), "wrong answer for part {} ({} variant): expected {}, got {:#}",
+                part, variant, expected, answer);
+    }
+}
@@ -1,35 +1,38 @@
 use anyhow::Result;
 use regex::{Regex, Captures};
 
-pub fn read_lines<F: FnMut(&str) -> Result<T>, T>(
+use crate::parse::Cursor;
+
+/// Parse each non-empty line of the input, after trimming whitespace
+///
+/// Errors from the parser which don't already point somewhere are pointed at the whole line.
+pub fn read_lines<F: FnMut(Cursor) -> Result<T>, T>(
     input: &mut dyn std::io::BufRead,
     mut parser: F
 ) -> Result<Vec<T>> {
-    let mut out = Vec::new();
-    let mut line = String::new();
-    while input.read_line(&mut line)? > 0 {
-        let trimmed = line.trim();
+    let data = crate::parse::read_all(input)?;
 
-        if !trimmed.is_empty() {
-            out.push((parser)(trimmed)?);
+    let mut out = Vec::new();
+    for line in Cursor::new(&data).lines().map(Cursor::trim) {
+        if !line.is_empty() {
+            let item = (parser)(line).map_err(|e| line.locate(line.rest(), e))?;
+            out.push(item);
         }
-        line.clear();
     }
 
     Ok(out)
 }
 
+/// Parse each non-empty line of the input, which must match a regex
 pub fn read_lines_regex<F: FnMut(Captures) -> Result<T>, T>(
     input: &mut dyn std::io::BufRead,
     expr: &str,
     mut parser: F
 ) -> Result<Vec<T>> {
     let expr = Regex::new(expr)?;
-    let mut lines = 0;
-    read_lines(input, |s| {
-        lines += 1;
-        let m = expr.captures(s)
-                    .ok_or_else(|| anyhow::anyhow!("No regex match on line {}", lines))?;
+    read_lines(input, |line| {
+        let m = expr.captures(line.rest())
+                    .ok_or_else(|| line.error_at(line.rest(), "Line doesn't match the expected format"))?;
         (parser)(m)
     })
 }
@@ -40,7 +43,7 @@ pub fn load_lines<T: std::str::FromStr>(
 ) -> Result<Vec<T>>
 where anyhow::Error: From<T::Err>,
 {
-    read_lines(input, |line| T::from_str(line).map_err(|e| e.into()))
+    read_lines(input, |line| T::from_str(line.rest()).map_err(|e| e.into()))
 }
 
 /// Load a grid containing data in each character
@@ -49,6 +52,19 @@ where anyhow::Error: From<T::Err>,
 pub fn load_grid<T: TryFrom<char>>(
     input: &mut dyn std::io::BufRead
 ) -> Result<crate::grid::Grid<T>>
+where T::Error: Send+Sync,
+      anyhow::Error: From<T::Error>,
+{
+    let data = crate::parse::read_all(input)?;
+    grid_from_lines(Cursor::new(&data).lines().map(Cursor::trim).filter(|l| !l.is_empty()))
+}
+
+/// Build a grid from lines of characters, which must all be the same width
+///
+/// Errors point at the offending line or character.
+pub fn grid_from_lines<'a, T: TryFrom<char>>(
+    lines: impl Iterator<Item=Cursor<'a>>
+) -> Result<crate::grid::Grid<T>>
 where T::Error: Send+Sync,
       anyhow::Error: From<T::Error>,
 {
@@ -57,28 +73,26 @@ where T::Error: Send+Sync,
     let mut data = Vec::new();
     let mut width = None;
 
-    let mut line = String::new();
-    while input.read_line(&mut line)? > 0 {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-
+    for line in lines {
+        let row = line.rest();
+        let len = row.chars().count();
         match width.as_ref() {
             Some(w) => {
-                anyhow::ensure!(*w == trimmed.len(), "Grid rows are not allowed to vary in width");
+                if *w != len {
+                    return Err(line.error_at(row, format!(
+                        "Grid rows are not allowed to vary in width (expected {}, found {})", w, len)));
+                }
             }
             None => {
-                width = Some(trimmed.len());
+                width = Some(len);
             }
         }
 
         // parse this line
-        for c in trimmed.chars() {
-            data.push(T::try_from(c)?);
+        for (i, c) in row.char_indices() {
+            let cell = T::try_from(c).map_err(|e| line.locate(&row[i..i + c.len_utf8()], e.into()))?;
+            data.push(cell);
         }
-
-        line.clear();
     }
 
     Ok(Grid::from_data(data, width.unwrap_or(0)))
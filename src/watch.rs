@@ -0,0 +1,46 @@
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// How often to check the watched file for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Modification time and size of a file, or `None` if it can't be read
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Call a function once, then again each time a file changes, until the process is interrupted
+///
+/// The screen is cleared before each call. Changes are detected by polling the file's
+/// modification time and size; a change is only acted on once the file has stopped changing, so
+/// that editors which write a file in several steps don't trigger several runs.
+pub fn watch<F: FnMut()>(path: &Path, mut func: F) -> ! {
+    let mut last = None;
+    let mut first = true;
+    loop {
+        let mut current = stamp(path);
+        if first || current != last {
+            // wait for the file to settle
+            loop {
+                std::thread::sleep(POLL_INTERVAL);
+                let next = stamp(path);
+                if next == current {
+                    break;
+                }
+                current = next;
+            }
+
+            print!("\x1b[2J\x1b[H");
+            println!("Watching {} for changes (Ctrl-C to stop)\n", path.display());
+            (func)();
+            let _ = std::io::stdout().flush();
+
+            last = current;
+            first = false;
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
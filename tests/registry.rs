@@ -0,0 +1,146 @@
+use aoc2022::solution::{ParamValues, Solution};
+
+#[test]
+fn solve_through_registry() {
+    assert_eq!(aoc2022::PROBLEMS.len(), 25);
+    assert_eq!(aoc2022::PROBLEMS[24].parts(), 1);
+
+    let input = "1000\n2000\n\n4000\n\n500\n";
+    let loaded = aoc2022::PROBLEMS[0].load(&mut input.as_bytes(), &ParamValues::default()).unwrap();
+    assert_eq!(loaded.solve(1).unwrap().to_string(), "4000");
+    assert_eq!(loaded.solve(2).unwrap().to_string(), "7500");
+    assert!(loaded.solve(3).is_err());
+}
+
+#[test]
+fn typed_solver() {
+    type P15 = aoc2022::p15::Solver;
+
+    let input = "Sensor at x=0, y=0: closest beacon is at x=2, y=0\n";
+    let mut values = ParamValues::default();
+    values.set("target_row", "1");
+
+    let params = P15::parse_params(&values).unwrap();
+    assert_eq!(params.target_row, 1);
+    assert_eq!(params.bound, 4_000_000);
+
+    // radius 2, so row 1 is covered from x=-1 to x=1
+    let readings = P15::parse(&mut input.as_bytes()).unwrap();
+    assert_eq!(P15::part1(&readings, &params).unwrap(), 3);
+}
+
+#[test]
+fn variants() {
+    let p12 = aoc2022::PROBLEMS[11];
+    assert_eq!(p12.variants(1), ["default", "bfs"]);
+    assert_eq!(p12.pick_variant(1, Some("bfs")), "bfs");
+    assert_eq!(p12.pick_variant(1, Some("missing")), "default");
+
+    let input = "SbcdefghijklmnopqrstuvwxyE\n";
+    let loaded = p12.load(&mut input.as_bytes(), &ParamValues::default()).unwrap();
+    assert_eq!(loaded.solve_variant(1, "bfs").unwrap(), loaded.solve(1).unwrap());
+    assert!(loaded.solve_variant(1, "missing").is_err());
+}
+
+#[test]
+fn generated_inputs() {
+    // keep the slower problems small
+    let mut params = ParamValues::default();
+    params.set("target_row", "10");
+    params.set("bound", "20");
+    params.set("minutes1", "16");
+    params.set("minutes2", "18");
+    for (idx, problem) in aoc2022::PROBLEMS.iter().enumerate() {
+        for seed in 0..3 {
+            let mut rng = aoc2022::gen::Rng::new(seed);
+            let input = problem.generate(&mut rng, 1, &params).unwrap()
+                               .unwrap_or_else(|| panic!("No generator for problem {}", idx + 1));
+            let loaded = problem.load(&mut input.as_bytes(), &params).unwrap();
+            for part in 1..=problem.parts() {
+                if let Err(e) = loaded.solve(part) {
+                    panic!("Problem {} part {} failed on seed {}: {}\n{}", idx + 1, part, seed, e, input);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn positive_answers() {
+    // day 20's coordinates can sum to anything, but day 6's marker is always somewhere
+    let p20 = aoc2022::PROBLEMS[19].load(&mut "0\n-1\n-2\n".as_bytes(), &ParamValues::default()).unwrap();
+    assert_eq!(p20.solve(1).unwrap().to_string(), "-3");
+
+    type P06 = aoc2022::p06::Solver;
+    assert_eq!(P06::POSITIVE, [1, 2]);
+}
+
+#[test]
+fn oversized_droplet() {
+    let input = "0,0,0\n100000,100000,100000\n";
+    let loaded = aoc2022::PROBLEMS[17].load(&mut input.as_bytes(), &ParamValues::default()).unwrap();
+    let err = loaded.solve(2).unwrap_err().to_string();
+    assert!(err.contains("too large for a dense grid"), "{}", err);
+}
+
+#[test]
+fn parse_error_positions() {
+    let cases: &[(usize, &str, (usize, usize))] = &[
+        (1, "1000\n\n20x0\n", (3, 1)),
+        (4, "2-4,6-8\n2-3,4_5\n", (2, 6)),
+        (11, "Monkey 0:\n  Starting items: 79\n  Operation: new = old ^ 19\n", (3, 24)),
+        (14, "498,4 -> 498,6 -> 496,6\n503,4 -> 502,5\n", (2, 10)),
+        (22, "  ..#\n  .x.\n\n10R5\n", (2, 4)),
+    ];
+
+    for (p_num, input, position) in cases {
+        let Err(e) = aoc2022::PROBLEMS[p_num - 1].load(&mut input.as_bytes(), &ParamValues::default()) else {
+            panic!("Problem {} loaded an invalid input", p_num);
+        };
+        let err = aoc2022::parse::find_error(&e)
+                 .unwrap_or_else(|| panic!("Problem {} error has no position: {}", p_num, e));
+        assert_eq!((err.line, err.column), *position, "{}", e);
+    }
+}
+
+#[test]
+fn inspect_state() {
+    let input = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
+                 move 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2\n";
+    let loaded = aoc2022::PROBLEMS[4].load(&mut input.as_bytes(), &ParamValues::default()).unwrap();
+
+    let mut state = loaded.inspect(2).unwrap();
+    let mut steps = 0;
+    while state.step().unwrap() {
+        steps += 1;
+    }
+    assert_eq!(steps, 4);
+    assert!(state.show().contains("[M] [C] [P]"), "{}", state.show());
+
+    assert!(loaded.inspect(3).is_err());
+    assert!(aoc2022::PROBLEMS[0].load(&mut "1\n".as_bytes(), &ParamValues::default())
+                                .unwrap().inspect(1).is_err());
+}
+
+#[test]
+fn record_frames() {
+    struct Frames(Vec<String>);
+
+    impl aoc2022::record::Recorder for Frames {
+        fn frame(&mut self, _origin: (isize, isize), cells: aoc2022::grid::Grid<char>) {
+            self.0.push(cells.render_with(|c| *c));
+        }
+    }
+
+    let input = "498,4 -> 498,6 -> 496,6\n503,4 -> 502,4 -> 502,9 -> 494,9\n";
+    let loaded = aoc2022::PROBLEMS[13].load(&mut input.as_bytes(), &ParamValues::default()).unwrap();
+
+    // a frame for each unit of sand that comes to rest
+    let mut frames = Frames(Vec::new());
+    loaded.record(1, &mut frames).unwrap();
+    assert_eq!(frames.0.len(), 24);
+    assert_eq!(frames.0[23].matches('o').count(), 24);
+
+    assert!(aoc2022::PROBLEMS[0].load(&mut "1\n".as_bytes(), &ParamValues::default())
+                                .unwrap().record(1, &mut frames).is_err());
+}